@@ -1,31 +1,60 @@
+use std::{collections::HashMap, time::Duration};
+
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    ApplicationSide, DirectionVector, GameState, Heavy, InputReceivedEvent, Lobby, Processing,
-    BALL_RADIUS,
+    map::Map,
+    mode::{FallOutcome, GameModes},
+    scene::Wall,
+    ApplicationSide, BallCollision, DirectionVector, EliminationEvent, GameState, Heavy,
+    InputReceivedEvent, Lobby, Processing, Ruleset, BALL_RADIUS, ELIMINATION_HEIGHT,
 };
 
-use self::heavy::HeavyPlugin;
-
-use crate::scene::Wall;
+use self::{collision::knock_back_balls, heavy::HeavyPlugin};
 
 /// Force applied to the ball when a key is pressed, in  kilogram pixel per second squared.
 const MOVEMENT_FORCE: f32 = 30.;
 const JUMP_SPEED: f32 = 25.;
 /// Y component of the direction vector that triggers jumping
 const JUMP_THRESHOLD: f32 = 0.2;
+/// Time during which the last player hitting a ball is credited with its elimination
+const ATTRIBUTION_WINDOW: Duration = Duration::from_secs(3);
 
+mod collision;
 mod heavy;
 
 #[derive(Component)]
 pub(super) struct Ball;
 
+/// Last player whose ball, or arrow, hit this ball hard enough to be credited with its fall
+#[derive(Component, Debug, Default)]
+pub(crate) struct LastHit {
+    player_id: Option<u64>,
+    /// Elapsed time of the app when the ball was hit
+    at: Duration,
+}
+
+impl LastHit {
+    pub(crate) fn record(&mut self, player_id: u64, time: &Time) {
+        self.player_id = Some(player_id);
+        self.at = time.elapsed();
+    }
+
+    /// Player credited with the elimination of the ball, if it was hit recently enough
+    fn killer(&self, time: &Time) -> Option<u64> {
+        self.player_id
+            .filter(|_| time.elapsed().saturating_sub(self.at) <= ATTRIBUTION_WINDOW)
+    }
+}
+
 pub(super) struct BallsPlugin;
 
 impl Plugin for BallsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(HeavyPlugin)
+            .add_event::<EliminationEvent>()
+            .add_event::<BallCollision>()
             .add_systems(
                 OnEnter(GameState::InGame),
                 (
@@ -47,50 +76,97 @@ impl Plugin for BallsPlugin {
                     .in_set(Processing)
                     .run_if(in_state(GameState::InGame)),
             )
+            .add_systems(
+                FixedUpdate,
+                (eliminate_fallen_balls, knock_back_balls)
+                    .in_set(Processing)
+                    .after(move_balls)
+                    .run_if(in_state(GameState::InGame))
+                    .run_if(resource_equals(ApplicationSide::Server)),
+            )
             .add_systems(OnExit(GameState::InGame), despawn_balls);
     }
 }
 
-pub(crate) fn dispatch_spawning_locations(mut lobby: ResMut<Lobby>) {
-    let locations = vec![Vec3::new(-100., 0., 0.), Vec3::new(100., 0., 0.)];
-    for (location, data) in locations.into_iter().zip(lobby.players.values_mut()) {
-        data.spawning_location = location;
+pub(crate) fn dispatch_spawning_locations(
+    mut lobby: ResMut<Lobby>,
+    map: Res<Map>,
+    ruleset: Res<Ruleset>,
+    modes: Res<GameModes>,
+) {
+    let mode = modes.get(ruleset.mode);
+    // Players handed a location so far, per team
+    let mut dispatched: HashMap<Option<u8>, usize> = HashMap::new();
+    for data in lobby.players.values_mut().filter(|data| !data.spectator) {
+        let index = dispatched.entry(data.team).or_default();
+        data.spawning_location = mode.spawn_location(&map, data.team, *index).extend(0.);
+        *index += 1;
     }
 }
 
-pub(super) fn spawn_balls(mut commands: Commands, mut lobby: ResMut<Lobby>) {
+pub(super) fn spawn_balls(
+    mut commands: Commands,
+    mut lobby: ResMut<Lobby>,
+    ruleset: Res<Ruleset>,
+    side: Res<ApplicationSide>,
+    modes: Res<GameModes>,
+) {
+    let simulating = *side == ApplicationSide::Server;
+    // The components of the modes are only needed in the rooms, which simulate the modes
+    let mode = simulating.then(|| modes.get(ruleset.mode));
     for data in lobby.players.values_mut() {
-        let entity = commands
-            .spawn((
-                Ball,
-                Heavy::default(),
-                TransformBundle::from_transform(Transform::from_translation(
-                    data.spawning_location,
-                )),
-                DirectionVector::default(),
-                RigidBody::Dynamic,
-                LockedAxes::ROTATION_LOCKED,
-                Collider::ball(BALL_RADIUS),
-                ExternalForce::default(),
-                ExternalImpulse::default(),
-                GravityScale(4.5),
-                AdditionalMassProperties::default(),
-                Sleeping::disabled(),
-                Restitution {
-                    coefficient: 1.,
-                    combine_rule: CoefficientCombineRule::Min,
-                },
+        // The clients are sent the balls of the server, which the players eliminated before a
+        // client joined the match no longer have
+        if data.spectator || (!simulating && data.entity.is_none()) {
+            continue;
+        }
+        let mut ball = commands.spawn((
+            Ball,
+            Heavy::default(),
+            TransformBundle::from_transform(Transform::from_translation(data.spawning_location)),
+            DirectionVector::default(),
+            RigidBody::Dynamic,
+            Velocity::default(),
+            LockedAxes::ROTATION_LOCKED,
+            Collider::ball(BALL_RADIUS),
+            ExternalForce::default(),
+            ExternalImpulse::default(),
+            GravityScale(4.5),
+            ColliderMassProperties::default(),
+            Sleeping::disabled(),
+            Restitution {
+                coefficient: 1.,
+                combine_rule: CoefficientCombineRule::Min,
+            },
+            (
                 Ccd::enabled(),
-            ))
-            .id();
-        data.entity = Some(entity);
+                ActiveEvents::COLLISION_EVENTS,
+                LastHit::default(),
+            ),
+        ));
+        if let (Some(team), false) = (data.team, ruleset.team_collisions) {
+            // Teammates go through each other but still hit the walls and the other teams
+            let group = Group::from_bits_truncate(1 << team);
+            ball.insert(CollisionGroups::new(group, Group::ALL ^ group));
+        }
+        if let Some(mode) = mode {
+            mode.equip_ball(&mut ball);
+        }
+        data.entity = Some(ball.id());
     }
 }
 
-pub(super) fn despawn_balls(mut commands: Commands, balls: Query<Entity, With<Ball>>) {
+pub(super) fn despawn_balls(
+    mut commands: Commands,
+    balls: Query<Entity, With<Ball>>,
+    mut lobby: ResMut<Lobby>,
+) {
     for ball in balls.iter() {
         commands.get_entity(ball).unwrap().despawn_recursive();
     }
+    for data in lobby.players.values_mut() {
+        data.entity = None;
+    }
 }
 
 fn choose_direction(
@@ -99,11 +175,13 @@ fn choose_direction(
     mut event_reader: EventReader<InputReceivedEvent>,
 ) {
     for InputReceivedEvent { origin, input } in event_reader.iter() {
-        // TODO unwraps
-        let mut direction = query
-            .get_mut(lobby.players.get(origin).unwrap().entity.unwrap())
-            .unwrap();
-        *direction = DirectionVector::from(*input);
+        // Eliminated players keep sending inputs but don't have a ball anymore
+        let Some(entity) = lobby.players.get(origin).and_then(|data| data.entity) else {
+            continue;
+        };
+        if let Ok(mut direction) = query.get_mut(entity) {
+            *direction = DirectionVector::from(*input);
+        }
     }
 }
 
@@ -119,11 +197,60 @@ fn jump(
     ctx: Res<RapierContext>,
 ) {
     for (ball, direction, mut ball_imp) in ball_query.iter_mut() {
-        let wall = wall_query.single();
-        if let Some(contact_pair) = ctx.contact_pair(ball, wall) {
-            if Vec2::from(*direction).y > JUMP_THRESHOLD && contact_pair.has_any_active_contacts() {
-                ball_imp.impulse = Vec2::Y * JUMP_SPEED;
+        if Vec2::from(*direction).y <= JUMP_THRESHOLD {
+            continue;
+        }
+        let touches_wall = wall_query.iter().any(|wall| {
+            ctx.contact_pair(ball, wall)
+                .is_some_and(|contact_pair| contact_pair.has_any_active_contacts())
+        });
+        if touches_wall {
+            ball_imp.impulse = Vec2::Y * JUMP_SPEED;
+        }
+    }
+}
+
+fn eliminate_fallen_balls(
+    mut commands: Commands,
+    mut lobby: ResMut<Lobby>,
+    mut query: Query<(&mut Transform, &mut Velocity, &LastHit), With<Ball>>,
+    mut event_writer: EventWriter<EliminationEvent>,
+    ruleset: Res<Ruleset>,
+    modes: Res<GameModes>,
+    time: Res<Time>,
+) {
+    let fall_outcome = modes.get(ruleset.mode).fall_outcome();
+    // The lobby is only borrowed mutably once a ball is eliminated, as it is broadcast to the
+    // clients whenever it changes
+    let mut eliminated = Vec::new();
+    for (&player_id, data) in lobby.players.iter() {
+        let Some(entity) = data.entity else {
+            continue;
+        };
+        let Ok((mut transform, mut velocity, last_hit)) = query.get_mut(entity) else {
+            continue;
+        };
+        if transform.translation.y >= ELIMINATION_HEIGHT {
+            continue;
+        }
+        match fall_outcome {
+            FallOutcome::Eliminate => {
+                commands.entity(entity).despawn_recursive();
+                eliminated.push(player_id);
+                event_writer.send(EliminationEvent {
+                    victim: player_id,
+                    killer: last_hit.killer(&time),
+                });
+            }
+            FallOutcome::Respawn => {
+                transform.translation = data.spawning_location;
+                *velocity = Velocity::zero();
             }
         }
     }
+    for player_id in eliminated {
+        if let Some(data) = lobby.players.get_mut(&player_id) {
+            data.entity = None;
+        }
+    }
 }
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{BallCollision, Heavy, Lobby, Ruleset};
+
+use super::{Ball, LastHit};
+
+/// Impulse given to a ball per pixel per second of relative velocity, before the ruleset factor
+const KNOCKBACK_FACTOR: f32 = 0.01;
+/// Factor applied to the knockback given by a heavy ball
+const HEAVY_KNOCKBACK_FACTOR: f32 = 2.;
+/// Collisions with a weaker impulse do not make the balls credited with the fall of each other
+const ATTRIBUTION_IMPULSE: f32 = 2.;
+
+/// Pushes apart the balls starting to touch, harder the faster they hit each other and when the
+/// hitting ball is heavy, and reports the collisions
+pub(super) fn knock_back_balls(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut balls: Query<
+        (
+            &Transform,
+            &Velocity,
+            &Heavy,
+            &mut ExternalImpulse,
+            &mut LastHit,
+        ),
+        With<Ball>,
+    >,
+    lobby: Res<Lobby>,
+    ruleset: Res<Ruleset>,
+    time: Res<Time>,
+    mut collision_writer: EventWriter<BallCollision>,
+) {
+    let player_of = |entity: Entity| {
+        lobby
+            .players
+            .iter()
+            .find(|(_, data)| data.entity == Some(entity))
+            .map(|(&id, _)| id)
+    };
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = *event else {
+            continue;
+        };
+        let Ok([first_ball, second_ball]) = balls.get_many_mut([first, second]) else {
+            continue;
+        };
+        let (first_transform, first_velocity, first_heavy, mut first_impulse, mut first_hit) =
+            first_ball;
+        let (second_transform, second_velocity, second_heavy, mut second_impulse, mut second_hit) =
+            second_ball;
+        let first_position = first_transform.translation.truncate();
+        let second_position = second_transform.translation.truncate();
+        let normal = (first_position - second_position).normalize_or_zero();
+        let relative_speed = (first_velocity.linvel - second_velocity.linvel).length();
+        let impulse = relative_speed * KNOCKBACK_FACTOR * ruleset.collision_knockback;
+        let hit_factor = |heavy: &Heavy| {
+            if heavy.heaviness {
+                HEAVY_KNOCKBACK_FACTOR
+            } else {
+                1.
+            }
+        };
+        first_impulse.impulse += normal * impulse * hit_factor(second_heavy);
+        second_impulse.impulse -= normal * impulse * hit_factor(first_heavy);
+
+        let (Some(first_player), Some(second_player)) = (player_of(first), player_of(second))
+        else {
+            continue;
+        };
+        if impulse >= ATTRIBUTION_IMPULSE {
+            first_hit.record(second_player, &time);
+            second_hit.record(first_player, &time);
+        }
+        collision_writer.send(BallCollision {
+            players: (first_player, second_player),
+            position: (first_position + second_position) / 2.,
+            impulse,
+        });
+    }
+}
@@ -1,21 +1,19 @@
-use std::time::Duration;
-
-use bevy::{prelude::*, time::Stopwatch};
+use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::{HeavinessReceivedEvent, Heavy, Lobby, Processing, HEAVINESS_DURATION};
-
-pub const HEAVINESS_FACTOR: f32 = 0.1;
+use crate::{ApplicationSide, HeavinessReceivedEvent, Heavy, Lobby, Processing, Ruleset};
 
 pub struct HeavyPlugin;
 
 impl Plugin for HeavyPlugin {
     fn build(&self, app: &mut App) {
+        // The stamina is simulated by the rooms and replicated to the clients
         app.add_systems(
             FixedUpdate,
-            (update_heavy, tick_timers, update_mass)
+            (update_heavy, update_stamina, update_mass)
                 .in_set(Processing)
-                .chain(),
+                .chain()
+                .run_if(resource_equals(ApplicationSide::Server)),
         );
     }
 }
@@ -26,38 +24,44 @@ fn update_heavy(
     mut event_reader: EventReader<HeavinessReceivedEvent>,
 ) {
     for HeavinessReceivedEvent { origin, heaviness } in event_reader.iter() {
-        // TODO : unwraps
-        dbg!("Heaviness received for entity", origin);
-        let mut heavy = query
-            .get_mut(lobby.players.get(origin).unwrap().entity.unwrap())
-            .unwrap();
-        heavy.heaviness = *heaviness;
+        let Some(entity) = lobby.players.get(origin).and_then(|data| data.entity) else {
+            continue;
+        };
+        if let Ok(mut heavy) = query.get_mut(entity) {
+            heavy.holding = *heaviness;
+        }
     }
 }
 
-fn tick_timers(mut query: Query<&mut Heavy>, time: Res<Time>) {
+/// Spends the stamina of the heavy balls and recovers the one of the others, locking the balls
+/// out of heaviness once they run out of it
+fn update_stamina(mut query: Query<&mut Heavy>, ruleset: Res<Ruleset>, time: Res<Time>) {
+    let delta = time.delta_seconds();
     for mut heavy in query.iter_mut() {
+        heavy.heaviness = heavy.holding && !heavy.exhausted;
         if heavy.heaviness {
-            heavy.heavy_timer.tick(time.delta());
-        } else if !heavy.heavy_timer.elapsed().is_zero() {
-            backwards_tick(&mut heavy.heavy_timer, time.delta())
+            heavy.stamina = (heavy.stamina - delta / ruleset.stamina_duration).max(0.);
+            if heavy.stamina == 0. {
+                heavy.exhausted = true;
+                heavy.heaviness = false;
+            }
+        } else {
+            heavy.stamina = (heavy.stamina + delta / ruleset.stamina_recovery).min(1.);
+            if heavy.stamina >= ruleset.exhaustion_threshold {
+                heavy.exhausted = false;
+            }
         }
     }
 }
 
-fn update_mass(mut query: Query<(&Heavy, &mut AdditionalMassProperties)>) {
-    for (heavy, mut additional_mass) in query.iter_mut() {
-        *additional_mass = if heavy.heaviness {
-            AdditionalMassProperties::Mass(
-                (HEAVINESS_DURATION.saturating_sub(heavy.heavy_timer.elapsed())).as_secs_f32()
-                    * HEAVINESS_FACTOR,
-            )
+fn update_mass(mut query: Query<(&Heavy, &mut ColliderMassProperties)>, ruleset: Res<Ruleset>) {
+    for (heavy, mut mass) in query.iter_mut() {
+        let density = if heavy.heaviness {
+            ruleset.heavy_mass_factor
         } else {
-            AdditionalMassProperties::default()
-        }
+            1.
+        };
+        // Rapier recomputes the mass of the colliders whose properties changed
+        mass.set_if_neq(ColliderMassProperties::Density(density));
     }
 }
-
-fn backwards_tick(timer: &mut Stopwatch, delta: Duration) {
-    timer.set_elapsed(timer.elapsed().saturating_sub(delta));
-}
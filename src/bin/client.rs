@@ -4,9 +4,9 @@ use bong::ClientPlugin;
 fn main() {
     App::new()
         .add_plugins(ClientPlugin {
-            server_addr: "127.0.0.1:5000".parse().unwrap(),
-            protocol_id: 1,
-            socket_addr: "127.0.0.1:0".parse().unwrap(),
+            // Without a server address given as argument, the servers of the local network are listed
+            server_addr: std::env::args().nth(1).map(|addr| addr.parse().unwrap()),
+            socket_addr: "0.0.0.0:0".parse().unwrap(),
         })
         .run();
 }
@@ -4,8 +4,7 @@ use bong::ClientPlugin;
 fn main() {
     App::new()
         .add_plugins(ClientPlugin {
-            server_addr: "127.0.0.1:5000".parse().unwrap(),
-            protocol_id: 1,
+            server_addr: Some("127.0.0.1:5000".parse().unwrap()),
             socket_addr: "127.0.0.1:1".parse().unwrap(),
         })
         .run();
@@ -0,0 +1,11 @@
+use bevy::prelude::*;
+use bong::PracticePlugin;
+
+fn main() {
+    App::new()
+        .add_plugins(PracticePlugin {
+            // Without a map name given as argument, the default map is played
+            map: std::env::args().nth(1),
+        })
+        .run();
+}
@@ -2,10 +2,15 @@ use bevy::prelude::*;
 use bong::server::ServerPlugin;
 
 fn main() {
+    // The address clients of the local network can reach the server at may be given as argument
+    let public_addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:5000".to_owned());
     App::new()
         .add_plugins(ServerPlugin {
-            public_addr: "127.0.0.1:5000".parse().unwrap(),
-            protocol_id: 1,
+            public_addr: public_addr.parse().unwrap(),
+            name: "bong server".to_owned(),
+            discovery: true,
         })
         .run();
 }
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     net::{SocketAddr, UdpSocket},
     time::SystemTime,
 };
@@ -15,32 +16,60 @@ use bevy_renet::{
     RenetClientPlugin,
 };
 use renet_visualizer::RenetClientVisualizer;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    ball::BallsPlugin, connection_config, display::DisplayPlugin, scene::GameScenePlugin,
-    ApplicationSide, GameState, Lobby, Processing, Receiving, Sending, FIXED_DT, PHYSICS_DT, PPM,
-    SUBSTEPS,
+    ball::BallsPlugin,
+    client_of, connection_config,
+    display::DisplayPlugin,
+    mode::GameModesPlugin,
+    profile::Profile,
+    protocol::{ProtocolInfo, NETCODE_PROTOCOL_ID},
+    scene::GameScenePlugin,
+    server::room::{RoomId, RoomInfo},
+    slot_of, ApplicationSide, GameState, Lobby, PlayerSlot, Processing, Receiving, Ruleset,
+    Sending, FIXED_DT, PHYSICS_DT, PPM, SUBSTEPS,
 };
 
-use self::communication::ClientCommunicationPlugin;
+use self::{
+    chat::ClientChatPlugin, communication::ClientCommunicationPlugin,
+    discovery::DiscoveryScannerPlugin, input::InputActionsPlugin, local::LocalPlayers,
+    ui::ClientUiPlugin,
+};
 
 pub mod channel;
+pub mod chat;
 pub mod communication;
+pub mod discovery;
+pub mod input;
+pub mod local;
+mod ui;
 
 pub struct ClientPlugin {
-    pub server_addr: SocketAddr,
+    /// Server to connect to on startup, the servers of the local network are listed otherwise
+    pub server_addr: Option<SocketAddr>,
     pub socket_addr: SocketAddr,
-    pub protocol_id: u64,
 }
 
 impl Plugin for ClientPlugin {
     fn build(&self, app: &mut App) {
-        let (client, transport) = self.new_renet_client();
+        let settings = ConnectionSettings {
+            socket_addr: self.socket_addr,
+        };
+        if let Some(server_addr) = self.server_addr {
+            let (client, transport, client_id) = settings.new_renet_client(server_addr);
+            app.insert_resource(client)
+                .insert_resource(transport)
+                .insert_resource(client_id);
+        }
         app.add_state::<GameState>()
             .insert_resource(Lobby::default())
-            .insert_resource(client)
+            .insert_resource(RoomBrowser::default())
+            .insert_resource(Profile::default())
+            .insert_resource(LocalPlayers::default())
+            .insert_resource(settings)
             .insert_resource(ApplicationSide::Client)
-            .insert_resource(transport)
+            .add_event::<ConnectEvent>()
             .insert_resource(FixedTime::new_from_secs(FIXED_DT))
             .insert_resource(RapierConfiguration {
                 timestep_mode: TimestepMode::Fixed {
@@ -71,15 +100,144 @@ impl Plugin for ClientPlugin {
                 OnEnter(GameState::InGame),
                 (Sending, Receiving, Processing).chain(),
             )
-            .add_plugins(ClientCommunicationPlugin)
-            .add_plugins((BallsPlugin, GameScenePlugin, DisplayPlugin))
-            .add_systems(Update, update_visualizer_system)
+            .add_plugins((
+                ClientCommunicationPlugin,
+                ClientChatPlugin,
+                ClientUiPlugin,
+                DiscoveryScannerPlugin,
+                InputActionsPlugin,
+            ))
+            .add_plugins((BallsPlugin, GameScenePlugin, DisplayPlugin, GameModesPlugin))
+            .add_systems(
+                OnEnter(GameState::InGame),
+                mark_local_player.after(Processing),
+            )
+            .add_systems(
+                Update,
+                (
+                    connect_to_server.run_if(not(resource_exists::<RenetClient>())),
+                    update_visualizer_system.run_if(resource_exists::<RenetClient>()),
+                    detect_disconnection.run_if(
+                        resource_exists::<RenetClient>()
+                            .and_then(not(resource_exists::<Disconnection>())),
+                    ),
+                ),
+            )
             .insert_resource(RenetClientVisualizer::<200>::default());
     }
 }
 
-impl ClientPlugin {
-    fn new_renet_client(&self) -> (RenetClient, NetcodeClientTransport) {
+/// Requests sent by the client on the reliable `ClientChannel::ClientMessages` channel
+///
+/// The requests about a single player carry the slot of the local player they are about
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    ListRooms,
+    CreateRoom {
+        name: String,
+        /// Password needed by other players to join, if any
+        password: Option<String>,
+        map: String,
+        ruleset: Ruleset,
+    },
+    /// Spectators can join full rooms and watch the running match
+    JoinRoom {
+        room_id: RoomId,
+        password: Option<String>,
+        spectator: bool,
+    },
+    JoinRoomByCode {
+        code: String,
+        password: Option<String>,
+        spectator: bool,
+    },
+    LeaveRoom,
+    /// Brings another local player in the room of the client, only accepted in the lobby
+    AddLocalPlayer {
+        slot: PlayerSlot,
+    },
+    RemoveLocalPlayer {
+        slot: PlayerSlot,
+    },
+    /// Name and color to be shown to the other players of the room
+    SetProfile {
+        slot: PlayerSlot,
+        profile: Profile,
+    },
+    SetReady {
+        slot: PlayerSlot,
+        ready: bool,
+    },
+    /// Only accepted in the lobby, and from spectators only if the room has room for a player
+    SetSpectator {
+        slot: PlayerSlot,
+        spectator: bool,
+    },
+    /// Team to play in, none to be put in a team when the match starts
+    ChooseTeam {
+        slot: PlayerSlot,
+        team: Option<u8>,
+    },
+    /// Only accepted from the host of the room, in the lobby
+    ChangeRoomSettings {
+        map: String,
+        ruleset: Ruleset,
+    },
+    /// Only accepted from the host of the room, every local player of the client of the player
+    /// being kicked along
+    KickPlayer {
+        player_id: u64,
+    },
+    /// Only accepted from the host of the room, once every other player is ready
+    StartMatch,
+}
+
+/// Room the client is currently playing in
+#[derive(Debug, Resource)]
+pub struct CurrentRoom {
+    pub info: RoomInfo,
+    pub host: Option<u64>,
+    pub ruleset: Ruleset,
+    /// Round trip times of the members of the room as last sent by the server, in milliseconds
+    pub pings: HashMap<u64, u32>,
+}
+
+/// Rooms of the server as last listed by the server, along with the last room error
+#[derive(Debug, Default, Resource)]
+pub struct RoomBrowser {
+    pub rooms: Vec<RoomInfo>,
+    pub error: Option<String>,
+}
+
+/// Reason for the connection to the server being closed
+#[derive(Debug, Resource)]
+pub struct Disconnection {
+    pub reason: String,
+}
+
+/// Id of this client on the server, which is also the key of its first local player in the `Lobby`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Resource)]
+pub struct LocalClientId(pub u64);
+
+/// Marks the balls of the local players of this client, along with their slot
+#[derive(Component)]
+pub struct LocalPlayer(pub PlayerSlot);
+
+/// Settings used to open the connection to a server
+#[derive(Debug, Resource)]
+pub struct ConnectionSettings {
+    pub socket_addr: SocketAddr,
+}
+
+/// Sent to connect to the server at the given address
+#[derive(Event)]
+pub struct ConnectEvent(pub SocketAddr);
+
+impl ConnectionSettings {
+    fn new_renet_client(
+        &self,
+        server_addr: SocketAddr,
+    ) -> (RenetClient, NetcodeClientTransport, LocalClientId) {
         let client = RenetClient::new(connection_config());
 
         let socket = UdpSocket::bind(self.socket_addr).unwrap();
@@ -89,15 +247,67 @@ impl ClientPlugin {
         let client_id = current_time.as_millis() as u64;
         let authentication = ClientAuthentication::Unsecure {
             client_id,
-            protocol_id: self.protocol_id,
-            server_addr: self.server_addr,
-            user_data: None,
+            protocol_id: NETCODE_PROTOCOL_ID,
+            server_addr,
+            user_data: Some(ProtocolInfo::current().to_user_data()),
         };
 
         let transport = NetcodeClientTransport::new(current_time, authentication, socket).unwrap();
 
-        (client, transport)
+        (client, transport, LocalClientId(client_id))
+    }
+}
+
+fn connect_to_server(
+    mut commands: Commands,
+    settings: Res<ConnectionSettings>,
+    mut connect_events: EventReader<ConnectEvent>,
+) {
+    if let Some(ConnectEvent(server_addr)) = connect_events.iter().last() {
+        println!("Connecting to {}", server_addr);
+        let (client, transport, client_id) = settings.new_renet_client(*server_addr);
+        commands.insert_resource(client);
+        commands.insert_resource(transport);
+        commands.insert_resource(client_id);
+    }
+}
+
+pub(crate) fn mark_local_player(
+    mut commands: Commands,
+    lobby: Res<Lobby>,
+    client_id: Option<Res<LocalClientId>>,
+) {
+    let Some(client_id) = client_id else {
+        return;
+    };
+    for (&player_id, data) in lobby.players.iter() {
+        if client_of(player_id) != client_id.0 {
+            continue;
+        }
+        if let Some(mut entity) = data.entity.and_then(|entity| commands.get_entity(entity)) {
+            entity.insert(LocalPlayer(slot_of(player_id)));
+        }
+    }
+}
+
+/// Explains the disconnections the server did not give a reason for, such as timeouts
+fn detect_disconnection(
+    mut commands: Commands,
+    client: Res<RenetClient>,
+    transport: Option<Res<NetcodeClientTransport>>,
+) {
+    if !client.is_disconnected() {
+        return;
     }
+    let reason = match (
+        transport.and_then(|transport| transport.disconnect_reason()),
+        client.disconnect_reason(),
+    ) {
+        (Some(reason), _) => reason.to_string(),
+        (None, Some(reason)) => reason.to_string(),
+        (None, None) => "Connection lost".to_owned(),
+    };
+    commands.insert_resource(Disconnection { reason });
 }
 
 fn update_visualizer_system(
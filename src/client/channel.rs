@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use bevy_renet::renet::{ChannelConfig, SendType};
 
 pub enum ClientChannel {
     PlayerInput,
     PlayerHeaviness,
+    ClientMessages,
+    Chat,
 }
 
 impl From<ClientChannel> for u8 {
@@ -10,6 +14,8 @@ impl From<ClientChannel> for u8 {
         match channel_id {
             ClientChannel::PlayerInput => 0,
             ClientChannel::PlayerHeaviness => 1,
+            ClientChannel::ClientMessages => 2,
+            ClientChannel::Chat => 3,
         }
     }
 }
@@ -26,6 +32,20 @@ impl ClientChannel {
                 max_memory_usage_bytes: 5 * 1024 * 1024,
                 send_type: SendType::Unreliable,
             },
+            ChannelConfig {
+                channel_id: Self::ClientMessages.into(),
+                max_memory_usage_bytes: 5 * 1024 * 1024,
+                send_type: SendType::ReliableOrdered {
+                    resend_time: Duration::from_millis(200),
+                },
+            },
+            ChannelConfig {
+                channel_id: Self::Chat.into(),
+                max_memory_usage_bytes: 1024 * 1024,
+                send_type: SendType::ReliableOrdered {
+                    resend_time: Duration::from_millis(200),
+                },
+            },
         ]
     }
 }
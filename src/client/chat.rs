@@ -0,0 +1,70 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetClient;
+
+use crate::server::{channel::ServerChannel, chat::ChatLine};
+
+use super::{channel::ClientChannel, CurrentRoom};
+
+/// Lines kept in the chat log, the oldest ones are dropped first
+const MAX_CHAT_LINES: usize = 50;
+
+pub(super) struct ClientChatPlugin;
+
+impl Plugin for ClientChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChatLog::default())
+            .insert_resource(ChatInput::default())
+            .add_systems(
+                Update,
+                (
+                    receive_chat_lines.run_if(resource_exists::<RenetClient>()),
+                    clear_chat_log.run_if(resource_removed::<CurrentRoom>()),
+                ),
+            );
+    }
+}
+
+/// Chat lines received in the current room
+#[derive(Debug, Default, Resource)]
+pub struct ChatLog {
+    pub lines: VecDeque<ChatLine>,
+    /// Players whose messages are hidden
+    pub muted: HashSet<u64>,
+}
+
+/// Message being written in the chat box
+#[derive(Debug, Default, Resource)]
+pub struct ChatInput {
+    pub draft: String,
+    /// Whether the chat box has the keyboard focus, the ball is not controlled meanwhile
+    pub typing: bool,
+}
+
+pub(crate) fn send_chat_message(client: &mut RenetClient, text: &str) {
+    let message = bincode::serialize(text).unwrap();
+    client.send_message(ClientChannel::Chat, message);
+}
+
+fn receive_chat_lines(mut client: ResMut<RenetClient>, mut log: ResMut<ChatLog>) {
+    while let Some(message) = client.receive_message(ServerChannel::Chat) {
+        let Ok(line) = bincode::deserialize::<ChatLine>(&message) else {
+            continue;
+        };
+        if line
+            .sender
+            .map_or(false, |sender| log.muted.contains(&sender))
+        {
+            continue;
+        }
+        if log.lines.len() >= MAX_CHAT_LINES {
+            log.lines.pop_front();
+        }
+        log.lines.push_back(line);
+    }
+}
+
+fn clear_chat_log(mut log: ResMut<ChatLog>) {
+    log.lines.clear();
+}
@@ -1,23 +1,22 @@
-use std::collections::HashMap;
-
-use bevy::{app::AppExit, prelude::*};
+use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_renet::renet::RenetClient;
 
 use crate::{
     ball::Ball,
-    client::channel::ClientChannel,
+    client::{
+        channel::ClientChannel,
+        input::{ActionState, InputAction},
+        local::{send_join, LocalPlayers},
+        ClientMessage, CurrentRoom, Disconnection, LocalPlayer, RoomBrowser,
+    },
+    mode::ModeStateSnapshot,
+    profile::Profile,
     server::{channel::ServerChannel, ServerMessage},
-    DirectionVector, GameState, HeavinessReceivedEvent, Heavy, InputReceivedEvent, Lobby,
-    PlayerInput, Receiving, Sending,
+    BallCollision, BodyKind, DirectionVector, EliminationEvent, GameState, HeavinessReceivedEvent,
+    Heavy, InputReceivedEvent, Lobby, NetworkedEntities, PlayerInput, Receiving, Ruleset, Sending,
+    FIRST_SLOT,
 };
 
-const KEY_UP: KeyCode = KeyCode::W;
-const KEY_DOWN: KeyCode = KeyCode::S;
-const KEY_LEFT: KeyCode = KeyCode::A;
-const KEY_RIGHT: KeyCode = KeyCode::D;
-const KEY_HEAVY: KeyCode = KeyCode::Space;
-
-// const MOVEMENT_KEYS: [KeyCode; 4] = [KEY_UP, KEY_DOWN, KEY_LEFT, KEY_RIGHT];
 pub struct ClientCommunicationPlugin;
 
 impl Plugin for ClientCommunicationPlugin {
@@ -34,10 +33,23 @@ impl Plugin for ClientCommunicationPlugin {
                 )
                     .run_if(in_state(GameState::InGame)),
             )
-            .add_systems(FixedUpdate, receive_server_message.in_set(Receiving));
+            .add_systems(OnExit(GameState::InGame), despawn_networked_bodies)
+            .add_systems(
+                FixedUpdate,
+                receive_server_message
+                    .in_set(Receiving)
+                    .run_if(resource_exists::<RenetClient>()),
+            );
     }
 }
 
+/// Body simulated on the server, identified by its entity there
+#[derive(Component, Debug)]
+pub(crate) struct NetworkedBody {
+    server_entity: u64,
+    pub(crate) kind: BodyKind,
+}
+
 pub fn receive_player_inputs(
     mut client: ResMut<RenetClient>,
     mut event_writer: EventWriter<InputReceivedEvent>,
@@ -65,62 +77,279 @@ fn receive_server_message(
     mut client: ResMut<RenetClient>,
     mut next_state: ResMut<NextState<GameState>>,
     mut lobby: ResMut<Lobby>,
-    mut exit: EventWriter<AppExit>,
+    mut browser: ResMut<RoomBrowser>,
+    profile: Res<Profile>,
+    local_players: Res<LocalPlayers>,
+    state: Res<State<GameState>>,
+    mut current_room: Option<ResMut<CurrentRoom>>,
+    mut elimination_writer: EventWriter<EliminationEvent>,
 ) {
     while let Some(message) = client.receive_message(ServerChannel::ServerMessages) {
-        // TODO unwrap
-        let message: ServerMessage = bincode::deserialize(&message).unwrap();
+        // Messages of a server speaking another protocol cannot be read, it rejects the client anyway
+        let Ok(message) = bincode::deserialize::<ServerMessage>(&message) else {
+            continue;
+        };
         match message {
+            ServerMessage::Rejected { reason } => {
+                client.disconnect();
+                commands.insert_resource(Disconnection { reason });
+            }
+            ServerMessage::RoomList { rooms } => browser.rooms = rooms,
+            ServerMessage::JoinedRoom { room } => {
+                println!("Joined room \"{}\"", room.name);
+                lobby.players.clear();
+                browser.error = None;
+                commands.insert_resource(CurrentRoom {
+                    ruleset: Ruleset {
+                        mode: room.mode,
+                        max_players: room.max_players,
+                        ..default()
+                    },
+                    info: room,
+                    host: None,
+                    pings: default(),
+                });
+                next_state.set(GameState::Lobby);
+                send_client_message(
+                    &mut client,
+                    &ClientMessage::SetProfile {
+                        slot: FIRST_SLOT,
+                        profile: profile.clone(),
+                    },
+                );
+                for player in local_players.0.iter() {
+                    send_join(&mut client, player);
+                }
+                // The next messages are about the room, they are read once it is inserted
+                break;
+            }
+            ServerMessage::LeftRoom => {
+                lobby.players.clear();
+                commands.remove_resource::<CurrentRoom>();
+                commands.remove_resource::<Ruleset>();
+                next_state.set(GameState::Lobby);
+            }
+            ServerMessage::LobbyUpdate {
+                players,
+                host,
+                map,
+                ruleset,
+            } => {
+                if let Some(room) = current_room.as_mut() {
+                    room.info.players = players.len();
+                    room.info.map = map;
+                    room.info.mode = ruleset.mode;
+                    room.info.max_players = ruleset.max_players;
+                    room.host = host;
+                    room.ruleset = ruleset;
+                }
+                if *state.get() == GameState::Lobby {
+                    lobby.players = players;
+                } else {
+                    // Balls are client entities, only the profiles and statistics are taken
+                    // during a match
+                    for (id, data) in players {
+                        let local_data = lobby.players.entry(id).or_default();
+                        local_data.profile = data.profile;
+                        local_data.stats = data.stats;
+                    }
+                }
+            }
+            ServerMessage::RoomError { reason } => browser.error = Some(reason),
             ServerMessage::EnterLobby => next_state.set(GameState::Lobby),
-            ServerMessage::EnterGame { players } => {
+            ServerMessage::EnterGame { players, map } => {
                 lobby.players = players;
+                commands.insert_resource(map);
+                // The shared simulation reads the rules of the match, as they were last sent
+                if let Some(room) = current_room.as_ref() {
+                    commands.insert_resource(room.ruleset.clone());
+                }
                 // entities are server entities but since they are immediately written away when balls are spawned this is not a problem
                 next_state.set(GameState::InGame);
             }
-            ServerMessage::Stop => exit.send(AppExit),
+            ServerMessage::EnterResults { results } => {
+                commands.insert_resource(results);
+                next_state.set(GameState::Results);
+            }
+            ServerMessage::Stop { reason } => {
+                client.disconnect();
+                commands.insert_resource(Disconnection { reason });
+            }
             ServerMessage::PlayerLeavedInGame { player_id } => {
-                // TODO unwraps
-                let entity = lobby.players.get(&player_id).unwrap().entity.unwrap();
-                commands.get_entity(entity).unwrap().despawn_recursive()
+                if let Some(entity) = lobby
+                    .players
+                    .remove(&player_id)
+                    .and_then(|data| data.entity)
+                {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            ServerMessage::Pings { pings } => {
+                if let Some(room) = current_room.as_mut() {
+                    room.pings = pings;
+                }
+            }
+            ServerMessage::PlayerEliminated { victim, killer } => {
+                if let Some(entity) = lobby
+                    .players
+                    .get_mut(&victim)
+                    .and_then(|data| data.entity.take())
+                {
+                    commands.entity(entity).despawn_recursive();
+                }
+                lobby.record_elimination(victim, killer);
+                elimination_writer.send(EliminationEvent { victim, killer });
             }
         }
     }
 }
 
-fn send_player_input(mut client: ResMut<RenetClient>, k_in: Res<Input<KeyCode>>) {
-    let mut direction = Vec2::ZERO;
-    for key in k_in.get_pressed() {
-        direction += match *key {
-            KEY_UP => Vec2::Y,
-            KEY_DOWN => Vec2::NEG_Y,
-            KEY_RIGHT => Vec2::X,
-            KEY_LEFT => Vec2::NEG_X,
-            _ => Vec2::ZERO,
-        }
-    }
-    let message = bincode::serialize(&PlayerInput { direction }).unwrap();
+pub(crate) fn send_client_message(client: &mut RenetClient, message: &ClientMessage) {
+    let message = bincode::serialize(message).unwrap();
+    client.send_message(ClientChannel::ClientMessages, message);
+}
+
+/// Sends the input of every local player, along with its slot
+fn send_player_input(
+    mut client: ResMut<RenetClient>,
+    actions: Res<ActionState>,
+    local_players: Res<LocalPlayers>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    local_balls: Query<(&Transform, &LocalPlayer)>,
+) {
+    let input = first_player_input(&actions, &windows, &cameras, &local_balls);
+    let message = bincode::serialize(&(FIRST_SLOT, input)).unwrap();
     client.send_message(ClientChannel::PlayerInput, message);
+    // The other local players have no cursor, they aim where they move without an aiming stick
+    for player in local_players.0.iter() {
+        let direction = player.actions.movement();
+        let aim = [player.actions.aim(), direction]
+            .into_iter()
+            .find(|aim| *aim != Vec2::ZERO)
+            .unwrap_or(Vec2::Y);
+        let input = PlayerInput {
+            direction,
+            aim,
+            action: player.actions.pressed(InputAction::Action),
+        };
+        let message = bincode::serialize(&(player.slot, input)).unwrap();
+        client.send_message(ClientChannel::PlayerInput, message);
+    }
 }
 
-fn send_player_heaviness(mut client: ResMut<RenetClient>, k_in: Res<Input<KeyCode>>) {
-    let message = bincode::serialize(&k_in.pressed(KEY_HEAVY)).unwrap();
-    client.send_message(ClientChannel::PlayerHeaviness, message)
+/// Input of the first local player, who aims with the aiming keys or else with the cursor
+pub(crate) fn first_player_input(
+    actions: &ActionState,
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+    local_balls: &Query<(&Transform, &LocalPlayer)>,
+) -> PlayerInput {
+    let direction = actions.movement();
+    let mut aim = actions.aim();
+    // The aiming keys take over the cursor, which is used when it is in the window
+    if aim == Vec2::ZERO {
+        let cursor = windows
+            .get_single()
+            .ok()
+            .and_then(|window| window.cursor_position())
+            .zip(cameras.get_single().ok())
+            .and_then(|(cursor, (camera, transform))| {
+                camera.viewport_to_world_2d(transform, cursor)
+            });
+        let ball = local_balls
+            .iter()
+            .find(|(_, player)| player.0 == FIRST_SLOT)
+            .map(|(transform, _)| transform);
+        aim = match (cursor, ball) {
+            (Some(cursor), Some(ball)) => cursor - ball.translation.truncate(),
+            _ => Vec2::Y,
+        };
+    }
+    PlayerInput {
+        direction,
+        aim,
+        action: actions.pressed(InputAction::Action),
+    }
+}
+
+fn send_player_heaviness(
+    mut client: ResMut<RenetClient>,
+    actions: Res<ActionState>,
+    local_players: Res<LocalPlayers>,
+) {
+    let heaviness = std::iter::once((FIRST_SLOT, &*actions))
+        .chain(
+            local_players
+                .0
+                .iter()
+                .map(|player| (player.slot, &player.actions)),
+        )
+        .map(|(slot, actions)| (slot, actions.pressed(InputAction::Heavy)));
+    for (slot, heavy) in heaviness {
+        let message = bincode::serialize(&(slot, heavy)).unwrap();
+        client.send_message(ClientChannel::PlayerHeaviness, message)
+    }
 }
 
 pub(crate) fn receive_networked_entities(
+    mut commands: Commands,
     mut client: ResMut<RenetClient>,
     lobby: Res<Lobby>,
     mut query: Query<(&mut Transform, &mut DirectionVector, &mut Heavy), With<Ball>>,
+    mut bodies: Query<(Entity, &NetworkedBody, &mut Transform), Without<Ball>>,
+    mut mode_state: ResMut<ModeStateSnapshot>,
+    mut collision_writer: EventWriter<BallCollision>,
 ) {
+    // Only the latest bodies are kept, spawned bodies are not queried before the next run
+    let mut latest_bodies = None;
     while let Some(message) = client.receive_message(ServerChannel::NetworkedEntities) {
-        let map: HashMap<u64, (Vec3, DirectionVector, bool)> =
-            bincode::deserialize(&message).unwrap();
-        for (id, (translation, new_direction, heaviness)) in map {
-            let entity = lobby.players.get(&id).unwrap().entity.unwrap();
-            let (mut transform, mut direction, mut heavy) = query.get_mut(entity).unwrap();
+        let entities: NetworkedEntities = bincode::deserialize(&message).unwrap();
+        for (id, (translation, new_direction, new_heavy)) in entities.balls {
+            let Some(entity) = lobby.players.get(&id).and_then(|data| data.entity) else {
+                continue;
+            };
+            let Ok((mut transform, mut direction, mut heavy)) = query.get_mut(entity) else {
+                continue;
+            };
             transform.translation = translation;
             *direction = new_direction;
-            heavy.heaviness = heaviness;
+            *heavy = new_heavy;
+        }
+        latest_bodies = Some(entities.bodies);
+        mode_state.0 = entities.mode_state;
+        collision_writer.send_batch(entities.collisions);
+    }
+    let Some(mut latest_bodies) = latest_bodies else {
+        return;
+    };
+    // Bodies missing from the snapshot have been despawned on the server, like the arrows
+    // that hit something
+    for (entity, body, mut transform) in bodies.iter_mut() {
+        match latest_bodies.remove(&body.server_entity) {
+            Some((_, translation, velocity)) => *transform = body_transform(translation, velocity),
+            None => commands.entity(entity).despawn_recursive(),
         }
     }
+    for (server_entity, (kind, translation, velocity)) in latest_bodies {
+        commands.spawn((
+            NetworkedBody {
+                server_entity,
+                kind,
+            },
+            TransformBundle::from_transform(body_transform(translation, velocity)),
+        ));
+    }
+}
+
+/// Points the body along its velocity, which only shows on the arrows
+fn body_transform(translation: Vec2, velocity: Vec2) -> Transform {
+    Transform::from_translation(translation.extend(0.))
+        .with_rotation(Quat::from_rotation_z(velocity.y.atan2(velocity.x)))
+}
+
+fn despawn_networked_bodies(mut commands: Commands, bodies: Query<Entity, With<NetworkedBody>>) {
+    for body in bodies.iter() {
+        commands.entity(body).despawn_recursive();
+    }
 }
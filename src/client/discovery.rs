@@ -0,0 +1,112 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetClient;
+
+use crate::discovery::{DiscoveryMessage, ServerAnnouncement, DISCOVERY_PORT};
+
+/// Time between two discovery queries
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+/// Servers that stopped answering for this long are removed from the list
+const SERVER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Looks for servers on the local network while the client is not connected
+pub struct DiscoveryScannerPlugin;
+
+impl Plugin for DiscoveryScannerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DiscoveredServers::default());
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).and_then(|socket| {
+            socket.set_broadcast(true)?;
+            socket.set_nonblocking(true)?;
+            Ok(socket)
+        });
+        match socket {
+            Ok(socket) => {
+                app.insert_resource(DiscoveryScanner {
+                    socket,
+                    last_query: None,
+                })
+                .add_systems(
+                    Update,
+                    scan_local_network.run_if(not(resource_exists::<RenetClient>())),
+                );
+            }
+            Err(error) => eprintln!("Could not start the discovery scanner: {}", error),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct DiscoveryScanner {
+    socket: UdpSocket,
+    last_query: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub struct DiscoveredServer {
+    /// Address of the game server
+    pub addr: SocketAddr,
+    pub announcement: ServerAnnouncement,
+    last_seen: Instant,
+}
+
+/// Servers that answered the last discovery queries
+#[derive(Debug, Default, Resource)]
+pub struct DiscoveredServers {
+    pub servers: Vec<DiscoveredServer>,
+}
+
+fn scan_local_network(
+    mut scanner: ResMut<DiscoveryScanner>,
+    mut discovered: ResMut<DiscoveredServers>,
+) {
+    if scanner
+        .last_query
+        .map_or(true, |last_query| last_query.elapsed() >= SCAN_INTERVAL)
+    {
+        let query = DiscoveryMessage::Query.encode();
+        // Servers running on this machine don't always receive broadcasts, so ask them directly
+        for ip in [Ipv4Addr::BROADCAST, Ipv4Addr::LOCALHOST] {
+            let _ = scanner.socket.send_to(&query, (ip, DISCOVERY_PORT));
+        }
+        scanner.last_query = Some(Instant::now());
+    }
+
+    let mut buffer = [0; 1024];
+    while let Ok((len, origin)) = scanner.socket.recv_from(&mut buffer) {
+        let Some(DiscoveryMessage::Announcement(announcement)) =
+            DiscoveryMessage::decode(&buffer[..len])
+        else {
+            continue;
+        };
+        let addr = SocketAddr::new(origin.ip(), announcement.port);
+        let server = DiscoveredServer {
+            addr,
+            announcement,
+            last_seen: Instant::now(),
+        };
+        // A server on this machine answers both on the loopback and on its network address, so
+        // servers are told apart by what they announce, keeping the network address
+        match discovered.servers.iter_mut().find(|known| {
+            known.announcement.name == server.announcement.name
+                && known.announcement.port == server.announcement.port
+        }) {
+            Some(known) => {
+                let addr = if addr.ip().is_loopback() {
+                    known.addr
+                } else {
+                    addr
+                };
+                *known = DiscoveredServer { addr, ..server };
+            }
+            None => discovered.servers.push(server),
+        }
+    }
+    discovered
+        .servers
+        .retain(|server| server.last_seen.elapsed() < SERVER_TIMEOUT);
+}
@@ -0,0 +1,526 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{chat::ChatInput, local::LocalPlayers};
+
+/// Files the settings are saved to, in the configuration directory of the user
+const BINDINGS_FILE: &str = "bindings.ron";
+const GAMEPAD_FILE: &str = "gamepad.ron";
+const CONFIG_DIR: &str = "bong";
+/// Part of the range of the sticks ignored around their center, so that worn sticks do not drift
+const DEFAULT_DEADZONE: f32 = 0.2;
+pub const MAX_DEADZONE: f32 = 0.9;
+
+pub(crate) struct InputActionsPlugin;
+
+impl Plugin for InputActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputBindings::load())
+            .insert_resource(GamepadConfig::load())
+            .init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystem));
+    }
+}
+
+/// What the player can do, whatever the keys or buttons they are bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Heavy,
+    /// Special action of the game mode, such as shooting an arrow
+    Action,
+    /// Aims without the mouse, which is used otherwise
+    AimUp,
+    AimDown,
+    AimLeft,
+    AimRight,
+    SwitchCamera,
+    /// Switches to the view of the next player still in the round, while spectating
+    NextView,
+    Scoreboard,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 13] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::Heavy,
+        InputAction::Action,
+        InputAction::AimUp,
+        InputAction::AimDown,
+        InputAction::AimLeft,
+        InputAction::AimRight,
+        InputAction::SwitchCamera,
+        InputAction::NextView,
+        InputAction::Scoreboard,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::MoveUp => "Move up",
+            InputAction::MoveDown => "Move down",
+            InputAction::MoveLeft => "Move left",
+            InputAction::MoveRight => "Move right",
+            InputAction::Heavy => "Heavy",
+            InputAction::Action => "Mode action",
+            InputAction::AimUp => "Aim up",
+            InputAction::AimDown => "Aim down",
+            InputAction::AimLeft => "Aim left",
+            InputAction::AimRight => "Aim right",
+            InputAction::SwitchCamera => "Switch camera",
+            InputAction::NextView => "Next player",
+            InputAction::Scoreboard => "Scoreboard",
+        }
+    }
+
+    /// Moving up makes the ball jump, which is done with the bottom face button of gamepads
+    fn default_bindings(self) -> Vec<Binding> {
+        use Binding::{Gamepad, Key};
+        match self {
+            InputAction::MoveUp => vec![
+                Key(KeyCode::W),
+                Key(KeyCode::Up),
+                Gamepad(GamepadButtonType::South),
+                Gamepad(GamepadButtonType::DPadUp),
+            ],
+            InputAction::MoveDown => vec![
+                Key(KeyCode::S),
+                Key(KeyCode::Down),
+                Gamepad(GamepadButtonType::DPadDown),
+            ],
+            InputAction::MoveLeft => vec![
+                Key(KeyCode::A),
+                Key(KeyCode::Left),
+                Gamepad(GamepadButtonType::DPadLeft),
+            ],
+            InputAction::MoveRight => vec![
+                Key(KeyCode::D),
+                Key(KeyCode::Right),
+                Gamepad(GamepadButtonType::DPadRight),
+            ],
+            InputAction::Heavy => vec![
+                Key(KeyCode::Space),
+                Key(KeyCode::X),
+                Gamepad(GamepadButtonType::West),
+                Gamepad(GamepadButtonType::RightTrigger2),
+            ],
+            InputAction::Action => vec![
+                Key(KeyCode::E),
+                Gamepad(GamepadButtonType::East),
+                Gamepad(GamepadButtonType::RightTrigger),
+            ],
+            InputAction::AimUp => vec![Key(KeyCode::I)],
+            InputAction::AimDown => vec![Key(KeyCode::K)],
+            InputAction::AimLeft => vec![Key(KeyCode::J)],
+            InputAction::AimRight => vec![Key(KeyCode::L)],
+            InputAction::SwitchCamera => {
+                vec![Key(KeyCode::C), Gamepad(GamepadButtonType::Select)]
+            }
+            InputAction::NextView => vec![Key(KeyCode::V), Gamepad(GamepadButtonType::North)],
+            InputAction::Scoreboard => vec![Key(KeyCode::Tab), Gamepad(GamepadButtonType::Start)],
+        }
+    }
+}
+
+/// Device a local player besides the first one plays with, the first one using the bindings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    /// Arrow keys to move, X to be heavy and Z for the action of the mode
+    ArrowKeys,
+    Gamepad(usize),
+}
+
+impl InputSource {
+    pub fn label(self, gamepads: &Gamepads) -> String {
+        match self {
+            InputSource::ArrowKeys => "Arrow keys".to_owned(),
+            InputSource::Gamepad(id) => {
+                let name = gamepads.name(Gamepad::new(id)).unwrap_or("Disconnected");
+                format!("{name} ({id})")
+            }
+        }
+    }
+
+    fn bindings(self) -> InputBindings {
+        match self {
+            InputSource::ArrowKeys => InputBindings(BTreeMap::from([
+                (InputAction::MoveUp, vec![Binding::Key(KeyCode::Up)]),
+                (InputAction::MoveDown, vec![Binding::Key(KeyCode::Down)]),
+                (InputAction::MoveLeft, vec![Binding::Key(KeyCode::Left)]),
+                (InputAction::MoveRight, vec![Binding::Key(KeyCode::Right)]),
+                (InputAction::Heavy, vec![Binding::Key(KeyCode::X)]),
+                (InputAction::Action, vec![Binding::Key(KeyCode::Z)]),
+            ])),
+            // The default buttons of the gamepads
+            InputSource::Gamepad(_) => InputBindings(
+                InputAction::ALL
+                    .into_iter()
+                    .map(|action| {
+                        let buttons = action
+                            .default_bindings()
+                            .into_iter()
+                            .filter(|binding| matches!(binding, Binding::Gamepad(_)))
+                            .collect();
+                        (action, buttons)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn gamepads(self) -> Vec<Gamepad> {
+        match self {
+            InputSource::ArrowKeys => Vec::new(),
+            InputSource::Gamepad(id) => vec![Gamepad::new(id)],
+        }
+    }
+}
+
+/// Key or button an action is bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Button of the gamepads chosen in the `GamepadConfig`
+    Gamepad(GamepadButtonType),
+}
+
+impl Binding {
+    pub fn label(self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+            Binding::Gamepad(button) => format!("Pad {:?}", button),
+        }
+    }
+}
+
+/// Bindings of every action, any of them triggers the action
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Resource)]
+pub struct InputBindings(pub BTreeMap<InputAction, Vec<Binding>>);
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self(
+            InputAction::ALL
+                .into_iter()
+                .map(|action| (action, action.default_bindings()))
+                .collect(),
+        )
+    }
+}
+
+impl InputBindings {
+    /// Reads the saved bindings, the actions missing from the file keeping their default
+    /// bindings
+    pub fn load() -> InputBindings {
+        let mut bindings = InputBindings::default();
+        if let Some(saved) = load_config::<InputBindings>(BINDINGS_FILE) {
+            bindings.0.extend(saved.0);
+        }
+        bindings
+    }
+
+    pub fn save(&self) {
+        save_config(BINDINGS_FILE, self);
+    }
+
+    pub fn get(&self, action: InputAction) -> &[Binding] {
+        self.0.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Shortest way to tell the player which key triggers the action
+    pub fn hint(&self, action: InputAction) -> String {
+        self.get(action)
+            .first()
+            .map_or_else(|| "Unbound".to_owned(), |binding| binding.label())
+    }
+}
+
+/// Gamepads read by the client, the sticks moving and aiming the ball
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Resource)]
+pub struct GamepadConfig {
+    /// Id of the only gamepad read, every connected gamepad is read if it is none or not
+    /// connected
+    pub gamepad: Option<usize>,
+    pub deadzone: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            gamepad: None,
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
+}
+
+impl GamepadConfig {
+    /// Brings the deadzone of an edited file back in the range the sticks can be scaled with
+    pub fn load() -> GamepadConfig {
+        let mut config = load_config::<GamepadConfig>(GAMEPAD_FILE).unwrap_or_default();
+        config.deadzone = if config.deadzone.is_finite() {
+            config.deadzone.clamp(0., MAX_DEADZONE)
+        } else {
+            DEFAULT_DEADZONE
+        };
+        config
+    }
+
+    pub fn save(&self) {
+        save_config(GAMEPAD_FILE, self);
+    }
+
+    pub fn reads(&self, gamepads: &Gamepads, gamepad: Gamepad) -> bool {
+        match self.gamepad {
+            Some(id) if gamepads.contains(Gamepad::new(id)) => gamepad.id == id,
+            _ => true,
+        }
+    }
+}
+
+fn config_path(file: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR).join(file))
+}
+
+/// Reads a configuration file, none if it is missing or invalid
+fn load_config<T: DeserializeOwned>(file: &str) -> Option<T> {
+    let path = config_path(file)?;
+    let content = fs::read_to_string(&path).ok()?;
+    ron::from_str(&content)
+        .map_err(|error| eprintln!("Could not read {}: {}", path.display(), error))
+        .ok()
+}
+
+fn save_config<T: Serialize>(file: &str, value: &T) {
+    let Some(path) = config_path(file) else {
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|error| error.to_string())
+        .and_then(|_| {
+            ron::ser::to_string_pretty(value, default()).map_err(|error| error.to_string())
+        })
+        .and_then(|content| fs::write(&path, content).map_err(|error| error.to_string()));
+    if let Err(error) = result {
+        eprintln!("Could not save {}: {}", path.display(), error);
+    }
+}
+
+/// Actions triggered during the frame, none while typing in the chat box or rebinding an action
+#[derive(Debug, Default, Resource)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+    /// Directions of the left and right sticks of the gamepads
+    movement_stick: Vec2,
+    aim_stick: Vec2,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Sum of the directions of the pressed actions
+    pub fn direction(
+        &self,
+        up: InputAction,
+        down: InputAction,
+        left: InputAction,
+        right: InputAction,
+    ) -> Vec2 {
+        [
+            (up, Vec2::Y),
+            (down, Vec2::NEG_Y),
+            (left, Vec2::NEG_X),
+            (right, Vec2::X),
+        ]
+        .into_iter()
+        .filter(|(action, _)| self.pressed(*action))
+        .map(|(_, direction)| direction)
+        .sum()
+    }
+
+    /// Direction the ball is pushed in, the sticks only jumping through the buttons bound to
+    /// `MoveUp` since the ball would otherwise jump whenever the stick is slightly tilted up
+    pub fn movement(&self) -> Vec2 {
+        self.direction(
+            InputAction::MoveUp,
+            InputAction::MoveDown,
+            InputAction::MoveLeft,
+            InputAction::MoveRight,
+        ) + Vec2::new(self.movement_stick.x, self.movement_stick.y.min(0.))
+    }
+
+    /// Direction the free camera is panned in, along the whole movement stick
+    pub fn pan(&self) -> Vec2 {
+        self.direction(
+            InputAction::MoveUp,
+            InputAction::MoveDown,
+            InputAction::MoveLeft,
+            InputAction::MoveRight,
+        ) + self.movement_stick
+    }
+
+    pub fn aim(&self) -> Vec2 {
+        self.direction(
+            InputAction::AimUp,
+            InputAction::AimDown,
+            InputAction::AimLeft,
+            InputAction::AimRight,
+        ) + self.aim_stick
+    }
+}
+
+/// Action waiting for the next key or button pressed to be bound to it, from the controls window
+#[derive(Debug, Default, Resource)]
+pub struct Rebinding(pub Option<InputAction>);
+
+/// State of the keyboard, mouse and gamepads during the frame
+struct InputDevices<'a> {
+    keys: &'a Input<KeyCode>,
+    mouse_buttons: &'a Input<MouseButton>,
+    gamepad_buttons: &'a Input<GamepadButton>,
+    axes: &'a Axis<GamepadAxis>,
+    deadzone: f32,
+}
+
+impl InputDevices<'_> {
+    /// Actions triggered by the bindings on the given gamepads, leaving out the excluded keys
+    fn read(
+        &self,
+        bindings: &InputBindings,
+        gamepads: &[Gamepad],
+        excluded_keys: &HashSet<KeyCode>,
+    ) -> ActionState {
+        let mut state = ActionState::default();
+        for &gamepad in gamepads {
+            state.movement_stick += self.stick(
+                gamepad,
+                GamepadAxisType::LeftStickX,
+                GamepadAxisType::LeftStickY,
+            );
+            state.aim_stick += self.stick(
+                gamepad,
+                GamepadAxisType::RightStickX,
+                GamepadAxisType::RightStickY,
+            );
+        }
+        for (&action, action_bindings) in bindings.0.iter() {
+            for binding in action_bindings {
+                let (pressed, just_pressed) = match *binding {
+                    Binding::Key(key) if excluded_keys.contains(&key) => (false, false),
+                    Binding::Key(key) => (self.keys.pressed(key), self.keys.just_pressed(key)),
+                    Binding::Mouse(button) => (
+                        self.mouse_buttons.pressed(button),
+                        self.mouse_buttons.just_pressed(button),
+                    ),
+                    Binding::Gamepad(button_type) => {
+                        let buttons = gamepads
+                            .iter()
+                            .map(|&gamepad| GamepadButton::new(gamepad, button_type));
+                        (
+                            self.gamepad_buttons.any_pressed(buttons.clone()),
+                            self.gamepad_buttons.any_just_pressed(buttons),
+                        )
+                    }
+                };
+                if pressed {
+                    state.pressed.insert(action);
+                }
+                if just_pressed {
+                    state.just_pressed.insert(action);
+                }
+            }
+        }
+        state
+    }
+
+    /// Position of the stick, scaled so that it starts from zero at the edge of the deadzone
+    fn stick(&self, gamepad: Gamepad, x: GamepadAxisType, y: GamepadAxisType) -> Vec2 {
+        let stick = Vec2::new(
+            self.axes
+                .get(GamepadAxis::new(gamepad, x))
+                .unwrap_or_default(),
+            self.axes
+                .get(GamepadAxis::new(gamepad, y))
+                .unwrap_or_default(),
+        );
+        let length = stick.length();
+        if length <= self.deadzone {
+            return Vec2::ZERO;
+        }
+        stick / length * ((length - self.deadzone) / (1. - self.deadzone)).min(1.)
+    }
+}
+
+/// Reads the actions of every local player, the keys and gamepads of the other local players
+/// being left out of the bindings of the first one
+fn update_action_state(
+    mut state: ResMut<ActionState>,
+    mut local_players: ResMut<LocalPlayers>,
+    bindings: Res<InputBindings>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+    gamepad_config: Res<GamepadConfig>,
+    chat: Res<ChatInput>,
+    rebinding: Res<Rebinding>,
+) {
+    *state = ActionState::default();
+    for player in local_players.0.iter_mut() {
+        player.actions = ActionState::default();
+    }
+    if chat.typing || rebinding.0.is_some() {
+        return;
+    }
+    let devices = InputDevices {
+        keys: &keys,
+        mouse_buttons: &mouse_buttons,
+        gamepad_buttons: &gamepad_buttons,
+        axes: &axes,
+        deadzone: gamepad_config.deadzone,
+    };
+    let mut claimed_keys = HashSet::new();
+    let mut claimed_gamepads = HashSet::new();
+    for player in local_players.0.iter_mut() {
+        let player_bindings = player.source.bindings();
+        let player_gamepads = player.source.gamepads();
+        player.actions = devices.read(&player_bindings, &player_gamepads, &HashSet::new());
+        claimed_gamepads.extend(player_gamepads);
+        claimed_keys.extend(player_bindings.0.values().flatten().filter_map(
+            |binding| match *binding {
+                Binding::Key(key) => Some(key),
+                _ => None,
+            },
+        ));
+    }
+    let read_gamepads: Vec<Gamepad> = gamepads
+        .iter()
+        .filter(|gamepad| {
+            gamepad_config.reads(&gamepads, *gamepad) && !claimed_gamepads.contains(gamepad)
+        })
+        .collect();
+    *state = devices.read(&bindings, &read_gamepads, &claimed_keys);
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use bevy_renet::renet::RenetClient;
+
+use crate::{
+    profile::{PlayerColor, Profile, PALETTE},
+    PlayerSlot, MAX_LOCAL_PLAYERS,
+};
+
+use super::{
+    communication::send_client_message,
+    input::{ActionState, InputSource},
+    ClientMessage,
+};
+
+/// Player playing on the same machine as the first local player, who plays with the bindings of
+/// the controls window
+#[derive(Debug)]
+pub struct ExtraPlayer {
+    pub slot: PlayerSlot,
+    pub source: InputSource,
+    pub profile: Profile,
+    /// Actions triggered by the player during the frame, read from its input source
+    pub actions: ActionState,
+}
+
+/// Other players playing on this machine, each of them being a player of its own on the server
+#[derive(Debug, Default, Resource)]
+pub struct LocalPlayers(pub Vec<ExtraPlayer>);
+
+impl LocalPlayers {
+    /// Adds a player in the first free slot, playing with the first input source nobody uses,
+    /// none if every slot is taken
+    pub fn add(&mut self, main_profile: &Profile, gamepads: &Gamepads) -> Option<&ExtraPlayer> {
+        let slot = (1..MAX_LOCAL_PLAYERS as PlayerSlot)
+            .find(|&slot| !self.0.iter().any(|player| player.slot == slot))?;
+        let mut gamepad_ids: Vec<usize> = gamepads.iter().map(|gamepad| gamepad.id).collect();
+        gamepad_ids.sort_unstable();
+        let source = std::iter::once(InputSource::ArrowKeys)
+            .chain(gamepad_ids.into_iter().map(InputSource::Gamepad))
+            .find(|&source| !self.0.iter().any(|player| player.source == source))
+            .unwrap_or(InputSource::ArrowKeys);
+        // The server picks another name or color if they are already taken
+        let profile = Profile {
+            name: format!("{} {}", main_profile.name, slot + 1),
+            color: PlayerColor((main_profile.color.0 + slot) % PALETTE.len() as u8),
+        };
+        self.0.push(ExtraPlayer {
+            slot,
+            source,
+            profile,
+            actions: default(),
+        });
+        self.0.last()
+    }
+}
+
+/// Asks the server to bring the player in the room of the client
+pub(crate) fn send_join(client: &mut RenetClient, player: &ExtraPlayer) {
+    send_client_message(client, &ClientMessage::AddLocalPlayer { slot: player.slot });
+    send_client_message(
+        client,
+        &ClientMessage::SetProfile {
+            slot: player.slot,
+            profile: player.profile.clone(),
+        },
+    );
+}
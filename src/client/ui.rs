@@ -0,0 +1,225 @@
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+use bevy_renet::renet::RenetClient;
+
+use crate::{
+    display::Spectated,
+    mode::{FootballScore, GameModes},
+    team::{team_color, team_name},
+    GameState, Lobby, MatchResults, PlayerData,
+};
+
+use super::{
+    input::{InputAction, InputBindings},
+    CurrentRoom, Disconnection, LocalPlayer,
+};
+
+mod chat;
+mod controls;
+mod feed;
+mod rooms;
+mod scoreboard;
+mod servers;
+
+pub(super) struct ClientUiPlugin;
+
+impl Plugin for ClientUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<feed::KillFeed>()
+            .init_resource::<scoreboard::ScoreboardShown>()
+            .init_resource::<controls::ControlsShown>()
+            .add_systems(OnEnter(GameState::InGame), controls::cancel_rebinding)
+            .add_systems(OnExit(GameState::InGame), feed::clear_kill_feed)
+            .add_systems(
+                Update,
+                (scoreboard::toggle_scoreboard, scoreboard::show_scoreboard)
+                    .chain()
+                    .run_if(resource_exists::<CurrentRoom>()),
+            )
+            .add_systems(
+                Update,
+                (feed::update_kill_feed, feed::show_kill_feed)
+                    .chain()
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    show_results.run_if(in_state(GameState::Results)),
+                    show_football_score.run_if(resource_exists::<FootballScore>()),
+                    (show_action_hint, show_spectator_hint).run_if(
+                        resource_exists::<CurrentRoom>().and_then(in_state(GameState::InGame)),
+                    ),
+                    show_disconnection.run_if(resource_exists::<Disconnection>()),
+                    controls::show_controls.run_if(not(in_state(GameState::InGame))),
+                    servers::show_server_browser.run_if(not(resource_exists::<RenetClient>())),
+                    rooms::show_room_browser.run_if(
+                        resource_exists::<RenetClient>()
+                            .and_then(not(resource_exists::<CurrentRoom>()))
+                            .and_then(not(resource_exists::<Disconnection>())),
+                    ),
+                    chat::show_chat.run_if(
+                        resource_exists::<RenetClient>()
+                            .and_then(resource_exists::<CurrentRoom>())
+                            .and_then(not(resource_exists::<Disconnection>())),
+                    ),
+                    rooms::show_current_room.run_if(
+                        resource_exists::<CurrentRoom>().and_then(in_state(GameState::Lobby)),
+                    ),
+                ),
+            );
+    }
+}
+
+fn to_egui_color(color: Color) -> egui::Color32 {
+    let [r, g, b, a] = color.as_rgba_u8();
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Color of the ball of the player, the one of their team if they have one
+fn player_color(data: &PlayerData) -> egui::Color32 {
+    to_egui_color(
+        data.team
+            .map_or_else(|| data.profile.color.color(), team_color),
+    )
+}
+
+fn show_results(
+    mut egui_contexts: EguiContexts,
+    results: Option<Res<MatchResults>>,
+    lobby: Res<Lobby>,
+) {
+    let Some(results) = results else {
+        return;
+    };
+    egui::Window::new("Results")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0., 20.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            match (results.winning_team, results.winner) {
+                (Some(team), _) => ui.heading(
+                    egui::RichText::new(format!("{} team wins!", team_name(team)))
+                        .color(to_egui_color(team_color(team))),
+                ),
+                (None, Some(winner)) => match lobby.players.get(&winner) {
+                    Some(data) => ui.heading(
+                        egui::RichText::new(format!("{} wins!", data.profile.name))
+                            .color(to_egui_color(data.profile.color.color())),
+                    ),
+                    None => ui.heading(format!("Player {} wins!", winner)),
+                },
+                (None, None) => ui.heading("Draw"),
+            };
+            if !results.team_scores.is_empty() {
+                ui.horizontal(|ui| {
+                    for (team, score) in results.team_scores.iter().enumerate() {
+                        ui.colored_label(
+                            to_egui_color(team_color(team as u8)),
+                            format!("{}: {}", team_name(team as u8), score),
+                        );
+                    }
+                });
+            }
+            ui.separator();
+            egui::Grid::new("rankings").striped(true).show(ui, |ui| {
+                ui.label("#");
+                ui.label("Player");
+                ui.label("Kills");
+                ui.label("Deaths");
+                ui.end_row();
+                for (rank, (id, stats)) in results.rankings.iter().enumerate() {
+                    // Players who left since are not shown
+                    let Some(data) = lobby.players.get(id) else {
+                        continue;
+                    };
+                    ui.label((rank + 1).to_string());
+                    ui.colored_label(player_color(data), &data.profile.name);
+                    ui.label(stats.kills.to_string());
+                    ui.label(stats.deaths.to_string());
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn show_football_score(mut egui_contexts: EguiContexts, score: Res<FootballScore>) {
+    egui::Area::new("football_score")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0., 10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for (team, goals) in score.goals.iter().enumerate() {
+                    if team > 0 {
+                        ui.heading("-");
+                    }
+                    ui.heading(
+                        egui::RichText::new(goals.to_string())
+                            .color(to_egui_color(team_color(team as u8))),
+                    );
+                }
+            });
+        });
+}
+
+/// Reminds the players what the action key does in the mode being played
+fn show_action_hint(
+    mut egui_contexts: EguiContexts,
+    current_room: Res<CurrentRoom>,
+    modes: Res<GameModes>,
+    bindings: Res<InputBindings>,
+    local_ball: Query<(), With<LocalPlayer>>,
+) {
+    let Some(action) = modes.get(current_room.ruleset.mode).action() else {
+        return;
+    };
+    if local_ball.is_empty() {
+        return;
+    }
+    egui::Area::new("action_hint")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0., -10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(format!("[{}] {action}", bindings.hint(InputAction::Action)));
+        });
+}
+
+/// Tells the clients without a ball whom they are watching
+fn show_spectator_hint(
+    mut egui_contexts: EguiContexts,
+    spectated: Res<Spectated>,
+    lobby: Res<Lobby>,
+    bindings: Res<InputBindings>,
+    local_ball: Query<(), With<LocalPlayer>>,
+) {
+    if !local_ball.is_empty() {
+        return;
+    }
+    let name = spectated
+        .0
+        .and_then(|id| lobby.players.get(&id))
+        .map_or("nobody", |data| data.profile.name.as_str());
+    egui::Area::new("spectator_hint")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0., -10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(format!(
+                "Spectating {name}   [{}] Next player",
+                bindings.hint(InputAction::NextView)
+            ));
+        });
+}
+
+fn show_disconnection(
+    mut egui_contexts: EguiContexts,
+    disconnection: Res<Disconnection>,
+    mut exit: EventWriter<AppExit>,
+) {
+    egui::Window::new("Disconnected")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(&disconnection.reason);
+            if ui.button("Quit").clicked() {
+                exit.send(AppExit);
+            }
+        });
+}
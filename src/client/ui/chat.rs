@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_renet::renet::RenetClient;
+
+use crate::{
+    client::chat::{send_chat_message, ChatInput, ChatLog},
+    server::chat::MAX_CHAT_MESSAGE_LENGTH,
+    Lobby,
+};
+
+use super::to_egui_color;
+
+/// Focuses the chat box, which sends the message when pressed again
+const KEY_CHAT: KeyCode = KeyCode::Return;
+const NOTICE_COLOR: egui::Color32 = egui::Color32::GRAY;
+
+pub(super) fn show_chat(
+    mut egui_contexts: EguiContexts,
+    mut client: ResMut<RenetClient>,
+    log: Res<ChatLog>,
+    mut input: ResMut<ChatInput>,
+    lobby: Res<Lobby>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    egui::Window::new("Chat")
+        .collapsible(false)
+        .resizable(false)
+        .default_width(300.)
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10., -10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(150.)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in log.lines.iter() {
+                        let Some(sender) = line.sender else {
+                            ui.colored_label(NOTICE_COLOR, &line.text);
+                            continue;
+                        };
+                        ui.horizontal_wrapped(|ui| {
+                            match lobby.players.get(&sender) {
+                                Some(data) => ui.colored_label(
+                                    to_egui_color(data.profile.color.color()),
+                                    format!("{}:", data.profile.name),
+                                ),
+                                None => ui.colored_label(NOTICE_COLOR, "Someone:"),
+                            };
+                            ui.label(&line.text);
+                        });
+                    }
+                });
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut input.draft)
+                    .hint_text("Press Enter to chat")
+                    .char_limit(MAX_CHAT_MESSAGE_LENGTH)
+                    .desired_width(f32::INFINITY),
+            );
+            let sent = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if sent {
+                let draft = std::mem::take(&mut input.draft);
+                if !draft.trim().is_empty() {
+                    send_chat_message(&mut client, &draft);
+                }
+            }
+            // The key press that sent the message must not focus the chat box again
+            let mut typing = response.has_focus();
+            if keyboard_input.just_pressed(KEY_CHAT) && !typing && !response.lost_focus() {
+                response.request_focus();
+                typing = true;
+            }
+            input.typing = typing;
+        });
+}
@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::client::input::{
+    Binding, GamepadConfig, InputAction, InputBindings, Rebinding, MAX_DEADZONE,
+};
+
+/// Cancels the rebinding of an action instead of being bound to it
+const KEY_CANCEL_REBINDING: KeyCode = KeyCode::Escape;
+
+#[derive(Debug, Default, Resource)]
+pub(super) struct ControlsShown(bool);
+
+/// Lists the bindings of every action, which can be added by pressing the new key or button and
+/// removed by clicking them, and the gamepad settings, saving them on every change
+pub(super) fn show_controls(
+    mut egui_contexts: EguiContexts,
+    mut shown: ResMut<ControlsShown>,
+    mut bindings: ResMut<InputBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut gamepad_config: ResMut<GamepadConfig>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+) {
+    if let Some(action) = rebinding.0 {
+        let pressed = keys
+            .get_just_pressed()
+            .next()
+            .map(|&key| Binding::Key(key))
+            .or_else(|| {
+                mouse_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|&button| Binding::Mouse(button))
+            })
+            .or_else(|| {
+                gamepad_buttons
+                    .get_just_pressed()
+                    .find(|button| gamepad_config.reads(&gamepads, button.gamepad))
+                    .map(|button| Binding::Gamepad(button.button_type))
+            });
+        match pressed {
+            Some(Binding::Key(KEY_CANCEL_REBINDING)) => rebinding.0 = None,
+            Some(binding) => {
+                let action_bindings = bindings.0.entry(action).or_default();
+                if !action_bindings.contains(&binding) {
+                    action_bindings.push(binding);
+                    bindings.save();
+                }
+                rebinding.0 = None;
+            }
+            None => (),
+        }
+    }
+    egui::Area::new("controls_button")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-10., -10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if ui.button("Controls").clicked() {
+                shown.0 = !shown.0;
+                rebinding.0 = None;
+            }
+        });
+    if !shown.0 {
+        return;
+    }
+    let mut changed = false;
+    let mut gamepad_changed = false;
+    egui::Window::new("Controls")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+                for action in InputAction::ALL {
+                    ui.label(action.label());
+                    ui.horizontal(|ui| {
+                        let action_bindings = bindings.0.entry(action).or_default();
+                        let mut removed = None;
+                        for (index, binding) in action_bindings.iter().enumerate() {
+                            if ui
+                                .button(binding.label())
+                                .on_hover_text("Click to remove")
+                                .clicked()
+                            {
+                                removed = Some(index);
+                            }
+                        }
+                        if let Some(index) = removed {
+                            action_bindings.remove(index);
+                            changed = true;
+                        }
+                        if rebinding.0 == Some(action) {
+                            ui.label(format!(
+                                "Press a key or button... ({:?} to cancel)",
+                                KEY_CANCEL_REBINDING
+                            ));
+                        } else if ui.button("+").clicked() {
+                            rebinding.0 = Some(action);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            let gamepad_name = |id: usize| {
+                let name = gamepads.name(Gamepad::new(id)).unwrap_or("Disconnected");
+                format!("{name} ({id})")
+            };
+            let selected = gamepad_config
+                .gamepad
+                .map_or_else(|| "Any".to_owned(), &gamepad_name);
+            egui::ComboBox::from_label("Gamepad")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    let mut choice = gamepad_config.gamepad;
+                    ui.selectable_value(&mut choice, None, "Any");
+                    let mut connected: Vec<usize> =
+                        gamepads.iter().map(|gamepad| gamepad.id).collect();
+                    connected.sort_unstable();
+                    for id in connected {
+                        ui.selectable_value(&mut choice, Some(id), gamepad_name(id));
+                    }
+                    if choice != gamepad_config.gamepad {
+                        gamepad_config.gamepad = choice;
+                        gamepad_changed = true;
+                    }
+                });
+            let response = ui.add(
+                egui::Slider::new(&mut gamepad_config.deadzone, 0.0..=MAX_DEADZONE)
+                    .text("Stick deadzone"),
+            );
+            // Saved once the slider is released rather than at every step
+            if response.drag_released() || (response.changed() && !response.dragged()) {
+                gamepad_changed = true;
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Reset to defaults").clicked() {
+                    *bindings = InputBindings::default();
+                    *gamepad_config = GamepadConfig::default();
+                    rebinding.0 = None;
+                    changed = true;
+                    gamepad_changed = true;
+                }
+                if ui.button("Close").clicked() {
+                    shown.0 = false;
+                    rebinding.0 = None;
+                }
+            });
+        });
+    if changed {
+        bindings.save();
+    }
+    if gamepad_changed {
+        gamepad_config.save();
+    }
+}
+
+/// The controls are not shown during a match, which would otherwise not get any input
+pub(super) fn cancel_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
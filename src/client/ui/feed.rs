@@ -0,0 +1,78 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{EliminationEvent, Lobby};
+
+use super::player_color;
+
+/// Time an elimination stays in the feed
+const FEED_ENTRY_DURATION: Duration = Duration::from_secs(5);
+const MAX_FEED_ENTRIES: usize = 5;
+
+/// Latest eliminations of the match, with the names and colors the players had back then
+#[derive(Debug, Default, Resource)]
+pub(super) struct KillFeed(VecDeque<FeedEntry>);
+
+#[derive(Debug)]
+struct FeedEntry {
+    killer: Option<(String, egui::Color32)>,
+    victim: (String, egui::Color32),
+    timer: Timer,
+}
+
+pub(super) fn update_kill_feed(
+    mut feed: ResMut<KillFeed>,
+    mut event_reader: EventReader<EliminationEvent>,
+    lobby: Res<Lobby>,
+    time: Res<Time>,
+) {
+    feed.0
+        .retain_mut(|entry| !entry.timer.tick(time.delta()).finished());
+    let name_of = |player_id: u64| {
+        lobby
+            .players
+            .get(&player_id)
+            .map(|data| (data.profile.name.clone(), player_color(data)))
+    };
+    for &EliminationEvent { victim, killer } in event_reader.iter() {
+        let Some(victim) = name_of(victim) else {
+            continue;
+        };
+        feed.0.push_back(FeedEntry {
+            killer: killer.and_then(name_of),
+            victim,
+            timer: Timer::new(FEED_ENTRY_DURATION, TimerMode::Once),
+        });
+        if feed.0.len() > MAX_FEED_ENTRIES {
+            feed.0.pop_front();
+        }
+    }
+}
+
+pub(super) fn show_kill_feed(mut egui_contexts: EguiContexts, feed: Res<KillFeed>) {
+    egui::Area::new("kill_feed")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10., 10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            for entry in feed.0.iter() {
+                ui.horizontal(|ui| {
+                    let (victim, victim_color) = &entry.victim;
+                    match &entry.killer {
+                        Some((killer, killer_color)) if killer != victim => {
+                            ui.colored_label(*killer_color, killer);
+                            ui.label("knocked out");
+                        }
+                        _ => {
+                            ui.label("Fell:");
+                        }
+                    }
+                    ui.colored_label(*victim_color, victim);
+                });
+            }
+        });
+}
+
+pub(super) fn clear_kill_feed(mut feed: ResMut<KillFeed>) {
+    feed.0.clear();
+}
@@ -0,0 +1,539 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_renet::renet::RenetClient;
+
+use crate::{
+    client::{
+        chat::ChatLog,
+        communication::send_client_message,
+        input::InputSource,
+        local::{send_join, LocalPlayers},
+        ClientMessage, CurrentRoom, LocalClientId, RoomBrowser,
+    },
+    client_of,
+    map::Map,
+    mode::GameModes,
+    player_id,
+    profile::{PlayerColor, Profile, PALETTE},
+    server::room::MAX_ROOM_PLAYERS,
+    team::{team_color, team_name},
+    Lobby, Mode, Ruleset, Teams, FIRST_SLOT, MAX_LOCAL_PLAYERS,
+};
+
+use super::to_egui_color;
+
+/// Content of the text fields of the room browser
+pub(super) struct RoomForm {
+    name: String,
+    password: String,
+    map: String,
+    ruleset: Ruleset,
+    code: String,
+    join_password: String,
+}
+
+impl Default for RoomForm {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            password: String::new(),
+            map: Map::default().name,
+            ruleset: Ruleset::default(),
+            code: String::new(),
+            join_password: String::new(),
+        }
+    }
+}
+
+fn optional(password: &str) -> Option<String> {
+    (!password.is_empty()).then(|| password.to_owned())
+}
+
+pub(super) fn show_room_browser(
+    mut egui_contexts: EguiContexts,
+    mut client: ResMut<RenetClient>,
+    browser: Res<RoomBrowser>,
+    modes: Res<GameModes>,
+    mut form: Local<RoomForm>,
+) {
+    egui::Window::new("Rooms")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            if !client.is_connected() {
+                ui.label("Connecting to the server...");
+                return;
+            }
+            if let Some(error) = &browser.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.horizontal(|ui| {
+                ui.heading("Available rooms");
+                if ui.button("Refresh").clicked() {
+                    send_client_message(&mut client, &ClientMessage::ListRooms);
+                }
+            });
+            egui::Grid::new("rooms").striped(true).show(ui, |ui| {
+                for header in ["Name", "Code", "Players", "Map", "Mode", "State", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for room in browser.rooms.iter() {
+                    if room.locked {
+                        ui.label(format!("🔒 {}", room.name));
+                    } else {
+                        ui.label(&room.name);
+                    }
+                    ui.label(&room.code);
+                    ui.label(format!("{}/{}", room.players, room.max_players));
+                    ui.label(&room.map);
+                    ui.label(format!("{:?}", room.mode));
+                    ui.label(format!("{:?}", room.state));
+                    let joinable = room.players < room.max_players;
+                    if ui
+                        .add_enabled(joinable, egui::Button::new("Join"))
+                        .clicked()
+                    {
+                        send_client_message(
+                            &mut client,
+                            &ClientMessage::JoinRoom {
+                                room_id: room.id,
+                                password: optional(&form.join_password),
+                                spectator: false,
+                            },
+                        );
+                    }
+                    if ui.button("Spectate").clicked() {
+                        send_client_message(
+                            &mut client,
+                            &ClientMessage::JoinRoom {
+                                room_id: room.id,
+                                password: optional(&form.join_password),
+                                spectator: true,
+                            },
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            egui::Grid::new("join_room").show(ui, |ui| {
+                ui.label("Room code");
+                ui.text_edit_singleline(&mut form.code);
+                for (label, spectator) in [("Join by code", false), ("Spectate", true)] {
+                    if ui.button(label).clicked() {
+                        send_client_message(
+                            &mut client,
+                            &ClientMessage::JoinRoomByCode {
+                                code: form.code.clone(),
+                                password: optional(&form.join_password),
+                                spectator,
+                            },
+                        );
+                    }
+                }
+                ui.end_row();
+                ui.label("Password");
+                ui.add(egui::TextEdit::singleline(&mut form.join_password).password(true));
+                ui.end_row();
+            });
+            ui.separator();
+            ui.heading("Create a room");
+            let form = &mut *form;
+            egui::Grid::new("create_room").show(ui, |ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut form.name);
+                ui.end_row();
+                room_settings_fields(ui, &mut form.map, &mut form.ruleset, &modes);
+                ui.label("Password");
+                ui.add(egui::TextEdit::singleline(&mut form.password).password(true));
+                ui.end_row();
+            });
+            if ui.button("Create").clicked() {
+                send_client_message(
+                    &mut client,
+                    &ClientMessage::CreateRoom {
+                        name: form.name.clone(),
+                        password: optional(&form.password),
+                        map: form.map.clone(),
+                        ruleset: form.ruleset.clone(),
+                    },
+                );
+            }
+        });
+}
+
+/// Rows of a grid editing the map and ruleset of a room
+fn room_settings_fields(
+    ui: &mut egui::Ui,
+    map: &mut String,
+    ruleset: &mut Ruleset,
+    modes: &GameModes,
+) {
+    ui.label("Map");
+    egui::ComboBox::from_id_source("map")
+        .selected_text(map.as_str())
+        .show_ui(ui, |ui| {
+            for builtin in Map::builtin() {
+                ui.selectable_value(map, builtin.name.clone(), builtin.name);
+            }
+        });
+    ui.end_row();
+    ui.label("Mode");
+    egui::ComboBox::from_id_source("mode")
+        .selected_text(format!("{:?}", ruleset.mode))
+        .show_ui(ui, |ui| {
+            for mode in Mode::ALL {
+                ui.selectable_value(&mut ruleset.mode, mode, format!("{:?}", mode));
+            }
+        });
+    ui.end_row();
+    ui.label("Teams");
+    egui::ComboBox::from_id_source("teams")
+        .selected_text(format!("{:?}", ruleset.teams))
+        .show_ui(ui, |ui| {
+            for teams in Teams::ALL {
+                ui.selectable_value(&mut ruleset.teams, teams, format!("{:?}", teams));
+            }
+        });
+    ui.end_row();
+    if ruleset.teams != Teams::FreeForAll {
+        ui.label("Team collisions");
+        ui.checkbox(&mut ruleset.team_collisions, "");
+        ui.end_row();
+    }
+    if ruleset.mode == Mode::Arrows {
+        ui.label("Lethal arrows");
+        ui.checkbox(&mut ruleset.lethal_arrows, "");
+        ui.end_row();
+    }
+    if ruleset.mode == Mode::Capture {
+        ui.label("Capture time");
+        ui.add(egui::Slider::new(&mut ruleset.capture_time, 5..=120).suffix(" s"));
+        ui.end_row();
+    }
+    if ruleset.mode == Mode::Football {
+        ui.label("Goals to win");
+        ui.add(egui::Slider::new(&mut ruleset.goals_to_win, 1..=10));
+        ui.end_row();
+    }
+    ui.label("Stamina");
+    ui.add(egui::Slider::new(&mut ruleset.stamina_duration, 1. ..=15.).suffix(" s"));
+    ui.end_row();
+    ui.label("Stamina recovery");
+    ui.add(egui::Slider::new(&mut ruleset.stamina_recovery, 1. ..=15.).suffix(" s"));
+    ui.end_row();
+    ui.label("Exhaustion threshold");
+    ui.add(egui::Slider::new(
+        &mut ruleset.exhaustion_threshold,
+        0. ..=1.,
+    ));
+    ui.end_row();
+    ui.label("Heavy mass factor");
+    ui.add(egui::Slider::new(&mut ruleset.heavy_mass_factor, 1. ..=10.));
+    ui.end_row();
+    ui.label("Collision knockback");
+    ui.add(egui::Slider::new(
+        &mut ruleset.collision_knockback,
+        0. ..=3.,
+    ));
+    ui.end_row();
+    // Raised along with the mode, which may not be playable alone
+    let least_players = modes.get(ruleset.mode).min_players();
+    ruleset.min_players = ruleset.min_players.max(least_players);
+    ruleset.max_players = ruleset.max_players.max(ruleset.min_players);
+    ui.label("Min players");
+    ui.add(egui::Slider::new(
+        &mut ruleset.min_players,
+        least_players..=ruleset.max_players,
+    ));
+    ui.end_row();
+    ui.label("Max players");
+    ui.add(egui::Slider::new(
+        &mut ruleset.max_players,
+        ruleset.min_players..=MAX_ROOM_PLAYERS,
+    ));
+    ui.end_row();
+}
+
+pub(super) fn show_current_room(
+    mut egui_contexts: EguiContexts,
+    mut client: ResMut<RenetClient>,
+    current_room: Res<CurrentRoom>,
+    browser: Res<RoomBrowser>,
+    lobby: Res<Lobby>,
+    client_id: Option<Res<LocalClientId>>,
+    mut profile: ResMut<Profile>,
+    mut local_players: ResMut<LocalPlayers>,
+    gamepads: Res<Gamepads>,
+    mut chat: ResMut<ChatLog>,
+    modes: Res<GameModes>,
+    // Settings being edited by the host, taken from the room when nothing is being edited
+    mut settings: Local<Option<(String, Ruleset)>>,
+) {
+    let room = &current_room.info;
+    let client_id = client_id.map(|client_id| client_id.0);
+    // The local players of the client of the host are hosting along
+    let is_host = client_id.is_some() && current_room.host.map(client_of) == client_id;
+    egui::Window::new(room.name.as_str())
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(10., 10.))
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.label(format!("Room code: {}", room.code));
+            ui.label(format!("Map: {}", room.map));
+            ui.label(format!("Mode: {:?}", room.mode));
+            if let Some(error) = &browser.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.separator();
+            ui.heading(format!(
+                "Players ({}/{})",
+                lobby.playing().count(),
+                room.max_players
+            ));
+            egui::Grid::new("players").striped(true).show(ui, |ui| {
+                for (&id, data) in lobby.players.iter() {
+                    let local = Some(client_of(id)) == client_id;
+                    let mut name = data.profile.name.clone();
+                    if Some(id) == current_room.host {
+                        name = format!("★ {}", name);
+                    }
+                    if local {
+                        name = format!("{} (you)", name);
+                    }
+                    ui.colored_label(to_egui_color(data.profile.color.color()), name);
+                    if current_room.ruleset.teams != Teams::FreeForAll {
+                        match data.team {
+                            Some(team) => {
+                                ui.colored_label(to_egui_color(team_color(team)), team_name(team))
+                            }
+                            None => ui.label("Auto"),
+                        };
+                    }
+                    ui.label(format!("{} K / {} D", data.stats.kills, data.stats.deaths));
+                    ui.label(if data.spectator {
+                        "Spectating"
+                    } else if data.ready {
+                        "Ready"
+                    } else {
+                        "Not ready"
+                    });
+                    if !local {
+                        let muted = chat.muted.contains(&id);
+                        if ui.button(if muted { "Unmute" } else { "Mute" }).clicked() {
+                            if muted {
+                                chat.muted.remove(&id);
+                            } else {
+                                chat.muted.insert(id);
+                            }
+                        }
+                    }
+                    if is_host && !local && ui.button("Kick").clicked() {
+                        send_client_message(
+                            &mut client,
+                            &ClientMessage::KickPlayer { player_id: id },
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+            let local_data = client_id.and_then(|id| lobby.players.get(&id));
+            let team_count = current_room.ruleset.teams.count();
+            // Spectators are not put in a team
+            if team_count > 0 && !local_data.map_or(false, |data| data.spectator) {
+                let current_team = local_data.and_then(|data| data.team);
+                ui.horizontal(|ui| {
+                    ui.label("Team");
+                    let choices = std::iter::once(None).chain((0..team_count).map(Some));
+                    for team in choices {
+                        let label = team.map_or("Auto", team_name);
+                        if ui.selectable_label(current_team == team, label).clicked()
+                            && current_team != team
+                        {
+                            send_client_message(
+                                &mut client,
+                                &ClientMessage::ChooseTeam {
+                                    slot: FIRST_SLOT,
+                                    team,
+                                },
+                            );
+                        }
+                    }
+                });
+            }
+            let mut spectator = local_data.map_or(false, |data| data.spectator);
+            if !spectator {
+                let mut ready = local_data.map_or(false, |data| data.ready);
+                // The local players are all ready at once
+                if ui.checkbox(&mut ready, "Ready").changed() {
+                    let slots = std::iter::once(FIRST_SLOT)
+                        .chain(local_players.0.iter().map(|player| player.slot));
+                    for slot in slots {
+                        send_client_message(&mut client, &ClientMessage::SetReady { slot, ready });
+                    }
+                }
+            }
+            if ui.checkbox(&mut spectator, "Spectate").changed() {
+                send_client_message(
+                    &mut client,
+                    &ClientMessage::SetSpectator {
+                        slot: FIRST_SLOT,
+                        spectator,
+                    },
+                );
+            }
+            ui.separator();
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                let response = ui.text_edit_singleline(&mut profile.name);
+                changed |= response.lost_focus();
+            });
+            ui.horizontal_wrapped(|ui| {
+                for index in 0..PALETTE.len() as u8 {
+                    let color = PlayerColor(index);
+                    let swatch = egui::RichText::new("⏺")
+                        .size(20.)
+                        .color(to_egui_color(color.color()));
+                    if ui
+                        .selectable_label(profile.color == color, swatch)
+                        .clicked()
+                    {
+                        profile.color = color;
+                        changed = true;
+                    }
+                }
+            });
+            if changed {
+                send_client_message(
+                    &mut client,
+                    &ClientMessage::SetProfile {
+                        slot: FIRST_SLOT,
+                        profile: profile.clone(),
+                    },
+                );
+            }
+            ui.separator();
+            ui.heading("Local players");
+            let mut removed = None;
+            for (index, player) in local_players.0.iter_mut().enumerate() {
+                let data = client_id
+                    .map(|client_id| player_id(client_id, player.slot))
+                    .and_then(|id| lobby.players.get(&id));
+                ui.horizontal(|ui| {
+                    match data {
+                        Some(data) => ui.colored_label(
+                            to_egui_color(data.profile.color.color()),
+                            &data.profile.name,
+                        ),
+                        None => ui.label(format!("{} (not in the room)", player.profile.name)),
+                    };
+                    egui::ComboBox::from_id_source(("input_source", player.slot))
+                        .selected_text(player.source.label(&gamepads))
+                        .show_ui(ui, |ui| {
+                            let sources = std::iter::once(InputSource::ArrowKeys).chain(
+                                gamepads
+                                    .iter()
+                                    .map(|gamepad| InputSource::Gamepad(gamepad.id)),
+                            );
+                            for source in sources {
+                                ui.selectable_value(
+                                    &mut player.source,
+                                    source,
+                                    source.label(&gamepads),
+                                );
+                            }
+                        });
+                    if let Some(data) = data.filter(|_| team_count > 0) {
+                        let current_team = data.team;
+                        egui::ComboBox::from_id_source(("team", player.slot))
+                            .selected_text(current_team.map_or("Auto", team_name))
+                            .show_ui(ui, |ui| {
+                                let choices =
+                                    std::iter::once(None).chain((0..team_count).map(Some));
+                                for team in choices {
+                                    let label = team.map_or("Auto", team_name);
+                                    if ui.selectable_label(current_team == team, label).clicked()
+                                        && current_team != team
+                                    {
+                                        send_client_message(
+                                            &mut client,
+                                            &ClientMessage::ChooseTeam {
+                                                slot: player.slot,
+                                                team,
+                                            },
+                                        );
+                                    }
+                                }
+                            });
+                    }
+                    // The room may have been full when the player was added
+                    if data.is_none() && ui.button("Join").clicked() {
+                        send_join(&mut client, player);
+                    }
+                    if ui.button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                let player = local_players.0.remove(index);
+                send_client_message(
+                    &mut client,
+                    &ClientMessage::RemoveLocalPlayer { slot: player.slot },
+                );
+            }
+            if local_players.0.len() + 1 < MAX_LOCAL_PLAYERS
+                && ui.button("Add local player").clicked()
+            {
+                if let Some(player) = local_players.add(&profile, &gamepads) {
+                    send_join(&mut client, player);
+                }
+            }
+            if is_host {
+                ui.separator();
+                ui.heading("Host");
+                let (map, ruleset) = settings
+                    .get_or_insert_with(|| (room.map.clone(), current_room.ruleset.clone()));
+                egui::Grid::new("room_settings").show(ui, |ui| {
+                    room_settings_fields(ui, map, ruleset, &modes);
+                });
+                let mut applied = false;
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        send_client_message(
+                            &mut client,
+                            &ClientMessage::ChangeRoomSettings {
+                                map: map.clone(),
+                                ruleset: ruleset.clone(),
+                            },
+                        );
+                        applied = true;
+                    }
+                    let everyone_ready = lobby
+                        .playing()
+                        .all(|(&id, data)| Some(client_of(id)) == client_id || data.ready);
+                    let enough_players =
+                        lobby.playing().count() >= current_room.ruleset.min_players;
+                    if ui
+                        .add_enabled(
+                            everyone_ready && enough_players,
+                            egui::Button::new("Start match"),
+                        )
+                        .clicked()
+                    {
+                        send_client_message(&mut client, &ClientMessage::StartMatch);
+                    }
+                });
+                if applied {
+                    *settings = None;
+                }
+            }
+            ui.separator();
+            if ui.button("Leave room").clicked() {
+                send_client_message(&mut client, &ClientMessage::LeaveRoom);
+            }
+        });
+}
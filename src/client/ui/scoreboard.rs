@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    client::{
+        input::{ActionState, InputAction},
+        CurrentRoom,
+    },
+    team::{team_color, team_name},
+    Lobby, Teams,
+};
+
+use super::{player_color, to_egui_color};
+
+#[derive(Debug, Default, Resource)]
+pub(super) struct ScoreboardShown(bool);
+
+pub(super) fn toggle_scoreboard(actions: Res<ActionState>, mut shown: ResMut<ScoreboardShown>) {
+    if actions.just_pressed(InputAction::Scoreboard) {
+        shown.0 = !shown.0;
+    }
+}
+
+/// Lists the players of the room with their statistics since they joined it
+pub(super) fn show_scoreboard(
+    mut egui_contexts: EguiContexts,
+    shown: Res<ScoreboardShown>,
+    current_room: Res<CurrentRoom>,
+    lobby: Res<Lobby>,
+) {
+    if !shown.0 {
+        return;
+    }
+    let teams = current_room.ruleset.teams != Teams::FreeForAll;
+    let mut players: Vec<_> = lobby.players.iter().collect();
+    players.sort_by_key(|(_, data)| {
+        (
+            data.team,
+            std::cmp::Reverse(data.stats.wins),
+            std::cmp::Reverse(data.stats.kills),
+            data.stats.deaths,
+        )
+    });
+    egui::Window::new("Scoreboard")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("scoreboard").striped(true).show(ui, |ui| {
+                ui.label("Player");
+                if teams {
+                    ui.label("Team");
+                }
+                ui.label("Wins");
+                ui.label("Kills");
+                ui.label("Deaths");
+                ui.label("Ping");
+                ui.end_row();
+                for (id, data) in players {
+                    ui.colored_label(player_color(data), &data.profile.name);
+                    if teams {
+                        match data.team {
+                            Some(team) => {
+                                ui.colored_label(to_egui_color(team_color(team)), team_name(team))
+                            }
+                            None => ui.label("-"),
+                        };
+                    }
+                    ui.label(data.stats.wins.to_string());
+                    ui.label(data.stats.kills.to_string());
+                    ui.label(data.stats.deaths.to_string());
+                    match current_room.pings.get(id) {
+                        Some(ping) => ui.label(format!("{} ms", ping)),
+                        None => ui.label("-"),
+                    };
+                    ui.end_row();
+                }
+            });
+        });
+}
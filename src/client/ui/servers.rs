@@ -0,0 +1,61 @@
+use std::net::SocketAddr;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    client::{discovery::DiscoveredServers, ConnectEvent},
+    protocol::protocol_id,
+};
+
+pub(super) fn show_server_browser(
+    mut egui_contexts: EguiContexts,
+    discovered: Res<DiscoveredServers>,
+    mut connect_events: EventWriter<ConnectEvent>,
+    mut address: Local<String>,
+) {
+    egui::Window::new("Servers")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            ui.heading("Local network");
+            if discovered.servers.is_empty() {
+                ui.label("Looking for servers...");
+            }
+            egui::Grid::new("servers").striped(true).show(ui, |ui| {
+                for header in ["Name", "Address", "Players", "Rooms", "Games", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for server in discovered.servers.iter() {
+                    let announcement = &server.announcement;
+                    ui.label(&announcement.name);
+                    ui.label(server.addr.to_string());
+                    ui.label(announcement.players.to_string());
+                    ui.label(announcement.rooms.to_string());
+                    ui.label(announcement.games_in_progress.to_string());
+                    if announcement.protocol_id == protocol_id() {
+                        if ui.button("Connect").clicked() {
+                            connect_events.send(ConnectEvent(server.addr));
+                        }
+                    } else {
+                        ui.label("Incompatible");
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Address");
+                ui.text_edit_singleline(&mut *address);
+                let parsed = address.trim().parse::<SocketAddr>();
+                if ui
+                    .add_enabled(parsed.is_ok(), egui::Button::new("Connect"))
+                    .clicked()
+                {
+                    connect_events.send(ConnectEvent(parsed.unwrap()));
+                }
+            });
+        });
+}
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+/// Port the servers listen to for discovery queries on the local network
+pub const DISCOVERY_PORT: u16 = 5001;
+/// Prefix of every discovery packet, telling them apart from unrelated traffic
+const DISCOVERY_MAGIC: &[u8; 4] = b"BONG";
+
+/// What a server tells about itself when answering a discovery query
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerAnnouncement {
+    pub name: String,
+    pub protocol_id: u64,
+    /// Port of the game server, on the address the announcement came from
+    pub port: u16,
+    pub players: usize,
+    pub rooms: usize,
+    pub games_in_progress: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum DiscoveryMessage {
+    Query,
+    Announcement(ServerAnnouncement),
+}
+
+impl DiscoveryMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut packet = DISCOVERY_MAGIC.to_vec();
+        packet.extend(bincode::serialize(self).unwrap());
+        packet
+    }
+
+    pub fn decode(packet: &[u8]) -> Option<Self> {
+        let message = packet.strip_prefix(DISCOVERY_MAGIC.as_slice())?;
+        bincode::deserialize(message).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement() -> ServerAnnouncement {
+        ServerAnnouncement {
+            name: "bong server".to_owned(),
+            protocol_id: 42,
+            port: 5000,
+            players: 3,
+            rooms: 2,
+            games_in_progress: 1,
+        }
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let query = DiscoveryMessage::decode(&DiscoveryMessage::Query.encode());
+        assert!(matches!(query, Some(DiscoveryMessage::Query)));
+        let packet = DiscoveryMessage::Announcement(announcement()).encode();
+        let Some(DiscoveryMessage::Announcement(decoded)) = DiscoveryMessage::decode(&packet)
+        else {
+            panic!("The announcement was not decoded");
+        };
+        assert_eq!(decoded.name, "bong server");
+        assert_eq!(decoded.protocol_id, 42);
+        assert_eq!(decoded.port, 5000);
+        assert_eq!(
+            (decoded.players, decoded.rooms, decoded.games_in_progress),
+            (3, 2, 1)
+        );
+    }
+
+    #[test]
+    fn foreign_or_truncated_packets_are_ignored() {
+        let mut packet = DiscoveryMessage::Announcement(announcement()).encode();
+        packet[0] = b'X';
+        assert!(DiscoveryMessage::decode(&packet).is_none());
+        let packet = DiscoveryMessage::Announcement(announcement()).encode();
+        assert!(DiscoveryMessage::decode(&packet[..packet.len() - 1]).is_none());
+        assert!(DiscoveryMessage::decode(&DISCOVERY_MAGIC[..2]).is_none());
+    }
+}
@@ -1,9 +1,18 @@
 use bevy::prelude::*;
 
-use crate::{Displaying, GameState, Processing};
+use crate::{
+    mode::{CaptureProgress, FootballScore, GrappleAnchors},
+    Displaying, GameState, Processing,
+};
 
 mod ball;
+mod body;
+mod camera;
+mod collision;
 mod scene;
+mod zone;
+
+pub(crate) use camera::Spectated;
 
 pub const BACKGROUND_COLOR: Color = Color::rgb(0.17, 0.24, 0.31);
 
@@ -13,7 +22,21 @@ impl Plugin for DisplayPlugin {
     fn build(&self, app: &mut App) {
         app.configure_set(PostUpdate, Displaying.after(Processing))
             .configure_set(OnEnter(GameState::InGame), Displaying.after(Processing))
+            .init_resource::<camera::CameraMode>()
+            .init_resource::<Spectated>()
             .add_systems(Startup, setup_camera)
+            .add_systems(
+                Update,
+                (
+                    camera::switch_camera_mode,
+                    camera::switch_spectated_player,
+                    camera::update_camera,
+                    camera::move_free_camera,
+                )
+                    .chain()
+                    .in_set(Displaying)
+                    .run_if(in_state(GameState::InGame)),
+            )
             .add_systems(
                 OnEnter(GameState::InGame),
                 ball::display_balls.in_set(Displaying),
@@ -21,9 +44,30 @@ impl Plugin for DisplayPlugin {
             .add_systems(
                 Update,
                 // Moving display_scene here because it doesn't render the spritebundles properly if called once
-                (ball::update_ball_colors, scene::display_scene)
+                (
+                    ball::update_ball_colors,
+                    ball::update_stamina_bars,
+                    ball::highlight_local_player,
+                    body::display_bodies,
+                    scene::display_scene,
+                    collision::display_collisions,
+                    collision::update_collision_particles,
+                )
                     .in_set(Displaying)
                     .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (
+                    zone::display_capture_zone.run_if(resource_exists::<CaptureProgress>()),
+                    zone::display_goals.run_if(resource_exists::<FootballScore>()),
+                    ball::display_ropes.run_if(resource_exists::<GrappleAnchors>()),
+                )
+                    .in_set(Displaying),
+            )
+            .add_systems(
+                OnExit(GameState::InGame),
+                (collision::despawn_collision_particles, camera::reset_camera),
             );
     }
 }
@@ -1,8 +1,26 @@
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::{
+    prelude::*,
+    sprite::{Anchor, MaterialMesh2dBundle},
+};
 
-use crate::{Heavy, Lobby, BALL_RADIUS, HEAVINESS_DURATION};
+use crate::{
+    client::LocalPlayer, mode::GrappleAnchors, team::team_color, Heavy, Lobby, BALL_RADIUS,
+};
 
-const BALL_COLOR: Color = Color::rgb(0.0, 0.38, 0.39);
+/// Distance between the center of a ball and the name tag above it
+const NAME_TAG_OFFSET: f32 = BALL_RADIUS + 16.;
+const NAME_TAG_FONT_SIZE: f32 = 18.;
+/// Width of the ring drawn around the ball of the local player
+const OUTLINE_WIDTH: f32 = 4.;
+const OUTLINE_COLOR: Color = Color::WHITE;
+const ROPE_COLOR: Color = Color::rgb(0.85, 0.75, 0.55);
+/// Distance between the center of a ball and the stamina bar below it
+const STAMINA_BAR_OFFSET: f32 = -(BALL_RADIUS + 10.);
+const STAMINA_BAR_SIZE: Vec2 = Vec2::new(40., 5.);
+const STAMINA_BAR_BACKGROUND: Color = Color::rgba(0., 0., 0., 0.5);
+const STAMINA_COLOR: Color = Color::rgb(0.3, 0.85, 0.4);
+/// Color of the stamina while the ball can't be heavy
+const EXHAUSTED_COLOR: Color = Color::rgb(0.85, 0.3, 0.3);
 
 #[derive(Component)]
 pub(super) struct BallDisplay {
@@ -10,6 +28,10 @@ pub(super) struct BallDisplay {
     original_material: Handle<ColorMaterial>,
 }
 
+/// Part of the stamina bar filled according to the stamina of the ball
+#[derive(Component)]
+pub(super) struct StaminaBar;
+
 pub(super) fn display_balls(
     mut commands: Commands,
     mut lobby: ResMut<Lobby>,
@@ -17,13 +39,17 @@ pub(super) fn display_balls(
     mut meshes: ResMut<Assets<Mesh>>,
 ) {
     for data in lobby.players.values_mut() {
-        let material = materials.add(BALL_COLOR.into());
+        let Some(mut ball) = data.entity.and_then(|entity| commands.get_entity(entity)) else {
+            continue;
+        };
+        // Teammates share the color of their team
+        let color = data
+            .team
+            .map_or_else(|| data.profile.color.color(), team_color);
+        let material = materials.add(color.into());
         let mesh = meshes.add(shape::Circle::new(BALL_RADIUS).into()).into();
-        let original_material = materials.add(BALL_COLOR.into());
-        // TODO unwraps
-        let entity = commands
-            .get_entity(data.entity.unwrap())
-            .unwrap()
+        let original_material = materials.add(color.into());
+        let entity = ball
             .insert((
                 BallDisplay {
                     material: material.clone(),
@@ -37,11 +63,106 @@ pub(super) fn display_balls(
                     ..default()
                 },
             ))
+            .with_children(|ball| {
+                ball.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        data.profile.name.clone(),
+                        TextStyle {
+                            font_size: NAME_TAG_FONT_SIZE,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., NAME_TAG_OFFSET, 1.),
+                    ..default()
+                });
+                ball.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: STAMINA_BAR_BACKGROUND,
+                        custom_size: Some(STAMINA_BAR_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0., STAMINA_BAR_OFFSET, 1.),
+                    ..default()
+                });
+                ball.spawn((
+                    StaminaBar,
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: STAMINA_COLOR,
+                            custom_size: Some(STAMINA_BAR_SIZE),
+                            anchor: Anchor::CenterLeft,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            -STAMINA_BAR_SIZE.x / 2.,
+                            STAMINA_BAR_OFFSET,
+                            1.5,
+                        ),
+                        ..default()
+                    },
+                ));
+            })
             .id();
         data.entity = Some(entity);
     }
 }
 
+pub(super) fn highlight_local_player(
+    mut commands: Commands,
+    query: Query<Entity, Added<LocalPlayer>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).with_children(|ball| {
+            ball.spawn(MaterialMesh2dBundle {
+                mesh: meshes
+                    .add(shape::Circle::new(BALL_RADIUS + OUTLINE_WIDTH).into())
+                    .into(),
+                material: materials.add(OUTLINE_COLOR.into()),
+                // Behind the ball
+                transform: Transform::from_xyz(0., 0., -0.5),
+                ..default()
+            });
+        });
+    }
+}
+
+/// Draws the ropes of the grappling balls up to their anchors
+pub(super) fn display_ropes(
+    mut gizmos: Gizmos,
+    anchors: Res<GrappleAnchors>,
+    lobby: Res<Lobby>,
+    query: Query<&Transform>,
+) {
+    for (id, &anchor) in anchors.0.iter() {
+        let Some(data) = lobby.players.get(id) else {
+            continue;
+        };
+        if let Some(Ok(transform)) = data.entity.map(|entity| query.get(entity)) {
+            gizmos.line_2d(transform.translation.truncate(), anchor, ROPE_COLOR);
+        }
+    }
+}
+
+pub(super) fn update_stamina_bars(
+    balls: Query<(&Heavy, &Children)>,
+    mut bars: Query<(&mut Transform, &mut Sprite), With<StaminaBar>>,
+) {
+    for (heavy, children) in balls.iter() {
+        let mut bars = bars.iter_many_mut(children);
+        while let Some((mut transform, mut sprite)) = bars.fetch_next() {
+            transform.scale.x = heavy.stamina;
+            sprite.color = if heavy.exhausted {
+                EXHAUSTED_COLOR
+            } else {
+                STAMINA_COLOR
+            };
+        }
+    }
+}
+
 pub(super) fn update_ball_colors(
     query: Query<(&BallDisplay, &Heavy)>,
     mut assets: ResMut<Assets<ColorMaterial>>,
@@ -49,10 +170,7 @@ pub(super) fn update_ball_colors(
     for (ball, heavy) in query.iter() {
         let color = if let Some(original_material) = assets.get(&ball.original_material) {
             if heavy.heaviness {
-                apply_saturation_ratio(
-                    original_material.color,
-                    heavy.heavy_timer.elapsed_secs() / HEAVINESS_DURATION.as_secs_f32(),
-                )
+                apply_saturation_ratio(original_material.color, 1. - heavy.stamina)
             } else {
                 original_material.color
             }
@@ -0,0 +1,36 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    client::communication::NetworkedBody,
+    mode::{ARROW_RADIUS, FOOTBALL_RADIUS},
+    BodyKind,
+};
+
+const ARROW_COLOR: Color = Color::rgb(0.9, 0.85, 0.7);
+const ARROW_LENGTH: f32 = 24.;
+const FOOTBALL_COLOR: Color = Color::WHITE;
+
+/// Adds display components to the bodies sent by the server
+pub(super) fn display_bodies(
+    mut commands: Commands,
+    query: Query<(Entity, &NetworkedBody, &Transform), Without<Handle<ColorMaterial>>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, body, transform) in query.iter() {
+        let (mesh, color) = match body.kind {
+            // Drawn along the x axis, which the transform points along the velocity
+            BodyKind::Arrow => (
+                shape::Quad::new(Vec2::new(ARROW_LENGTH, ARROW_RADIUS)).into(),
+                ARROW_COLOR,
+            ),
+            BodyKind::Football => (shape::Circle::new(FOOTBALL_RADIUS).into(), FOOTBALL_COLOR),
+        };
+        commands.entity(entity).insert(MaterialMesh2dBundle {
+            mesh: meshes.add(mesh).into(),
+            material: materials.add(color.into()),
+            transform: *transform,
+            ..default()
+        });
+    }
+}
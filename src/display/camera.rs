@@ -0,0 +1,220 @@
+use bevy::{input::mouse::MouseWheel, prelude::*, render::camera::ScalingMode};
+
+use crate::{
+    ball::Ball,
+    client::{
+        input::{ActionState, InputAction},
+        LocalPlayer,
+    },
+    map::Map,
+    Lobby,
+};
+
+/// Speed of the free camera, in views per second
+const PAN_SPEED: f32 = 0.8;
+/// Factor applied to the area seen by the free camera per notch of the mouse wheel
+const ZOOM_STEP: f32 = 0.9;
+/// Space kept around what the camera frames, in pixels
+const FRAMING_MARGIN: f32 = 100.;
+/// Area seen around the ball of the local player when following it
+const FOLLOW_VIEW_SIZE: Vec2 = Vec2::new(800., 600.);
+/// Zoom limits of the dynamic camera, as the smallest and biggest areas it can frame
+const MIN_VIEW_SIZE: Vec2 = Vec2::new(600., 400.);
+const MAX_VIEW_SIZE: Vec2 = Vec2::new(2400., 1600.);
+/// Zoom limits of the free camera
+const MIN_FREE_VIEW_SIZE: Vec2 = Vec2::new(300., 200.);
+const MAX_FREE_VIEW_SIZE: Vec2 = Vec2::new(4000., 3000.);
+/// How fast the camera catches up with its target, per second
+const CAMERA_SMOOTHING: f32 = 5.;
+
+/// What the camera frames during a match, switched with the camera action
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Resource)]
+pub(super) enum CameraMode {
+    /// The whole map
+    #[default]
+    FitMap,
+    /// The ball of the local player, or of the spectated player when the local players have none
+    FollowPlayer,
+    /// Every ball still in the round
+    Dynamic,
+    /// Moved with the movement actions and zoomed with the mouse wheel, only while spectating
+    Free,
+}
+
+impl CameraMode {
+    fn next(self, spectating: bool) -> CameraMode {
+        match self {
+            CameraMode::FitMap => CameraMode::FollowPlayer,
+            CameraMode::FollowPlayer => CameraMode::Dynamic,
+            CameraMode::Dynamic if spectating => CameraMode::Free,
+            CameraMode::Dynamic | CameraMode::Free => CameraMode::FitMap,
+        }
+    }
+}
+
+/// Player followed by the camera of the clients without a ball, such as the spectators, the
+/// players eliminated from the round and the ones who joined during the match
+#[derive(Debug, Default, Resource)]
+pub(crate) struct Spectated(pub(crate) Option<u64>);
+
+pub(super) fn switch_camera_mode(
+    actions: Res<ActionState>,
+    mut mode: ResMut<CameraMode>,
+    local_ball: Query<(), With<LocalPlayer>>,
+) {
+    if actions.just_pressed(InputAction::SwitchCamera) {
+        *mode = mode.next(local_ball.is_empty());
+    }
+}
+
+/// Keeps the spectated player among the ones still in the round, switching to the next one
+/// with the next view action
+pub(super) fn switch_spectated_player(
+    actions: Res<ActionState>,
+    lobby: Res<Lobby>,
+    mut spectated: ResMut<Spectated>,
+) {
+    let mut alive: Vec<u64> = lobby
+        .players
+        .iter()
+        .filter(|(_, data)| data.entity.is_some())
+        .map(|(&id, _)| id)
+        .collect();
+    alive.sort_unstable();
+    let position = spectated
+        .0
+        .and_then(|id| alive.iter().position(|&alive| alive == id));
+    let next = actions.just_pressed(InputAction::NextView);
+    let new = match position {
+        Some(position) if next => alive.get((position + 1) % alive.len()).copied(),
+        Some(_) => spectated.0,
+        None => alive.first().copied(),
+    };
+    if spectated.0 != new {
+        spectated.0 = new;
+    }
+}
+
+/// Moves the camera smoothly towards the area framed by the mode, the projection keeping the
+/// aspect ratio of the window whatever its size
+pub(super) fn update_camera(
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mode: Res<CameraMode>,
+    map: Option<Res<Map>>,
+    balls: Query<&Transform, (With<Ball>, Without<Camera2d>)>,
+    local_balls: Query<(&Transform, &LocalPlayer), Without<Camera2d>>,
+    lobby: Res<Lobby>,
+    spectated: Res<Spectated>,
+    time: Res<Time>,
+) {
+    let Ok((mut transform, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let spectating = local_balls.is_empty();
+    if *mode == CameraMode::Free && spectating {
+        return;
+    }
+    // The first local player still in the round is followed when several play on this client
+    let local_ball = local_balls
+        .iter()
+        .min_by_key(|(_, player)| player.0)
+        .map(|(transform, _)| transform);
+    let followed = local_ball.or_else(|| {
+        let entity = lobby.players.get(&spectated.0?)?.entity?;
+        balls.get(entity).ok()
+    });
+    let map_view = map.map_or(
+        Rect::from_center_size(Vec2::ZERO, FOLLOW_VIEW_SIZE),
+        |map| {
+            let bounds = map.bounds();
+            Rect::from_center_size(
+                bounds.center(),
+                bounds.size() + Vec2::splat(2. * FRAMING_MARGIN),
+            )
+        },
+    );
+    let target = match *mode {
+        CameraMode::FitMap | CameraMode::Free => map_view,
+        CameraMode::FollowPlayer => followed.map_or(map_view, |ball| {
+            Rect::from_center_size(ball.translation.truncate(), FOLLOW_VIEW_SIZE)
+        }),
+        CameraMode::Dynamic => balls
+            .iter()
+            .map(|ball| {
+                let position = ball.translation.truncate();
+                Rect::from_corners(position, position)
+            })
+            .reduce(|bounds, rect| bounds.union(rect))
+            .map_or(map_view, |bounds| {
+                let size = (bounds.size() + Vec2::splat(2. * FRAMING_MARGIN))
+                    .clamp(MIN_VIEW_SIZE, MAX_VIEW_SIZE);
+                Rect::from_center_size(bounds.center(), size)
+            }),
+    };
+    let (center, size) = match projection.scaling_mode {
+        ScalingMode::AutoMin {
+            min_width,
+            min_height,
+        } => {
+            let blend = 1. - (-CAMERA_SMOOTHING * time.delta_seconds()).exp();
+            (
+                transform
+                    .translation
+                    .truncate()
+                    .lerp(target.center(), blend),
+                Vec2::new(min_width, min_height).lerp(target.size(), blend),
+            )
+        }
+        // Jumps to the target when the match starts
+        _ => (target.center(), target.size()),
+    };
+    transform.translation = center.extend(transform.translation.z);
+    projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: size.x,
+        min_height: size.y,
+    };
+}
+
+/// Pans the free camera with the movement actions and zooms it with the mouse wheel
+pub(super) fn move_free_camera(
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mode: Res<CameraMode>,
+    local_ball: Query<(), With<LocalPlayer>>,
+    actions: Res<ActionState>,
+    mut wheel_events: EventReader<MouseWheel>,
+    time: Res<Time>,
+) {
+    let notches: f32 = wheel_events.iter().map(|event| event.y.signum()).sum();
+    if *mode != CameraMode::Free || !local_ball.is_empty() {
+        return;
+    }
+    let Ok((mut transform, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+    let ScalingMode::AutoMin {
+        min_width,
+        min_height,
+    } = projection.scaling_mode
+    else {
+        return;
+    };
+    let size = (Vec2::new(min_width, min_height) * ZOOM_STEP.powf(notches))
+        .clamp(MIN_FREE_VIEW_SIZE, MAX_FREE_VIEW_SIZE);
+    let direction = actions.pan();
+    transform.translation += (direction * size * PAN_SPEED * time.delta_seconds()).extend(0.);
+    projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: size.x,
+        min_height: size.y,
+    };
+}
+
+/// Puts the camera back at the origin, at the scale of the window, for the menus
+pub(super) fn reset_camera(
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+) {
+    let Ok((mut transform, mut projection)) = camera.get_single_mut() else {
+        return;
+    };
+    transform.translation = Vec3::new(0., 0., transform.translation.z);
+    projection.scaling_mode = ScalingMode::WindowSize(1.);
+}
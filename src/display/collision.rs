@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use bevy::{
+    audio::{Pitch, PitchBundle, Volume},
+    prelude::*,
+};
+
+use crate::BallCollision;
+
+/// Collisions with a weaker impulse are too soft to be noticed
+const MIN_IMPULSE: f32 = 1.;
+/// Impulse of the collisions giving the loudest sound and the most particles
+const MAX_IMPULSE: f32 = 10.;
+const MAX_PARTICLES: usize = 12;
+const PARTICLE_SIZE: f32 = 5.;
+const PARTICLE_SPEED: f32 = 250.;
+const PARTICLE_LIFETIME: Duration = Duration::from_millis(400);
+const PARTICLE_COLOR: Color = Color::rgb(1., 0.95, 0.8);
+/// Frequencies of the sounds of the softest and of the hardest collisions, in hertz
+const SOFT_FREQUENCY: f32 = 180.;
+const HARD_FREQUENCY: f32 = 90.;
+const SOUND_DURATION: Duration = Duration::from_millis(60);
+
+#[derive(Component)]
+pub(super) struct CollisionParticle {
+    velocity: Vec2,
+    lifetime: Timer,
+}
+
+/// Spawns sparks and plays a thud where the balls hit each other, scaled by the impulse
+pub(super) fn display_collisions(
+    mut commands: Commands,
+    mut collisions: EventReader<BallCollision>,
+    mut pitches: ResMut<Assets<Pitch>>,
+) {
+    for collision in collisions.iter() {
+        if collision.impulse < MIN_IMPULSE {
+            continue;
+        }
+        let strength = (collision.impulse / MAX_IMPULSE).min(1.);
+        let count = (strength * MAX_PARTICLES as f32).ceil() as usize;
+        for i in 0..count {
+            let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+            commands.spawn((
+                CollisionParticle {
+                    velocity: Vec2::from_angle(angle) * PARTICLE_SPEED * strength,
+                    lifetime: Timer::new(PARTICLE_LIFETIME, TimerMode::Once),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: PARTICLE_COLOR,
+                        custom_size: Some(Vec2::splat(PARTICLE_SIZE)),
+                        ..default()
+                    },
+                    // Above the balls
+                    transform: Transform::from_translation(collision.position.extend(2.)),
+                    ..default()
+                },
+            ));
+        }
+        let frequency = SOFT_FREQUENCY + (HARD_FREQUENCY - SOFT_FREQUENCY) * strength;
+        commands.spawn(PitchBundle {
+            source: pitches.add(Pitch::new(frequency, SOUND_DURATION)),
+            settings: PlaybackSettings {
+                volume: Volume::new_relative(strength),
+                ..PlaybackSettings::DESPAWN
+            },
+        });
+    }
+}
+
+/// Moves the particles outwards while fading them out
+pub(super) fn update_collision_particles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut CollisionParticle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.);
+        sprite.color.set_a(particle.lifetime.percent_left());
+    }
+}
+
+/// Removes the particles left when the match ends
+pub(super) fn despawn_collision_particles(
+    mut commands: Commands,
+    query: Query<Entity, With<CollisionParticle>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,81 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    client::CurrentRoom,
+    map::Map,
+    mode::{CaptureProgress, Contender},
+    team::team_color,
+    Lobby,
+};
+
+const ZONE_COLOR: Color = Color::rgba(1., 1., 1., 0.6);
+const CONTESTED_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+/// Distance between the zone and the progress ring around it
+const RING_MARGIN: f32 = 20.;
+
+/// Draws the goals of the football mode in the color of the team defending them
+pub(super) fn display_goals(mut gizmos: Gizmos, map: Res<Map>) {
+    let goals = map.pitch.iter().flat_map(|pitch| pitch.goals.iter());
+    for (team, goal) in goals.enumerate() {
+        gizmos.rect_2d(
+            goal.position,
+            0.,
+            goal.half_size * 2.,
+            team_color(team as u8),
+        );
+    }
+}
+
+/// Draws the capture zone with a ring showing the progress of its holder, or of the leading
+/// contender when nobody holds it
+pub(super) fn display_capture_zone(
+    mut gizmos: Gizmos,
+    map: Res<Map>,
+    progress: Res<CaptureProgress>,
+    lobby: Res<Lobby>,
+    current_room: Option<Res<CurrentRoom>>,
+) {
+    let Some(zone) = &map.capture_zone else {
+        return;
+    };
+    let contender_color = |contender: Contender| match contender {
+        Contender::Player(id) => lobby
+            .players
+            .get(&id)
+            .map_or(ZONE_COLOR, |data| data.profile.color.color()),
+        Contender::Team(team) => team_color(team),
+    };
+    let zone_color = if progress.contested {
+        CONTESTED_COLOR
+    } else {
+        progress.holder.map_or(ZONE_COLOR, contender_color)
+    };
+    gizmos.rect_2d(zone.position, 0., zone.half_size * 2., zone_color);
+    let radius = zone.half_size.length() + RING_MARGIN;
+    gizmos.circle_2d(zone.position, radius, ZONE_COLOR.with_a(0.2));
+    let shown = progress.holder.or_else(|| {
+        progress
+            .scores
+            .iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(&contender, _)| contender)
+    });
+    let (Some(contender), Some(room)) = (shown, current_room) else {
+        return;
+    };
+    let score = progress.scores.get(&contender).copied().unwrap_or_default();
+    let ratio = (score / room.ruleset.capture_time as f32).min(1.);
+    let arc = ratio * TAU;
+    // Fills clockwise from the top
+    gizmos
+        .arc_2d(
+            zone.position,
+            arc / 2.,
+            arc,
+            radius,
+            contender_color(contender),
+        )
+        .segments(64);
+}
@@ -1,21 +1,31 @@
 use std::{collections::HashMap, time::Duration};
 
-use bevy::{prelude::*, time::Stopwatch};
+use bevy::prelude::*;
 
 pub mod client;
+pub mod practice;
 pub mod server;
 
 mod ball;
+mod discovery;
 mod display;
+mod map;
+mod mode;
+mod profile;
+mod protocol;
 mod scene;
+mod team;
 
 use bevy_renet::renet::ConnectionConfig;
 use client::channel::ClientChannel;
 pub use client::ClientPlugin;
 use derive_more::Mul;
+pub use practice::PracticePlugin;
+use profile::Profile;
 use serde::{Deserialize, Serialize};
 use server::channel::ServerChannel;
 pub use server::ServerPlugin;
+pub use team::Teams;
 
 pub const PPM: f32 = 100.;
 pub const FIXED_DT: f32 = 0.02;
@@ -24,17 +34,17 @@ pub const PHYSICS_DT: f32 = 1. * FIXED_DT;
 pub const SUBSTEPS: usize = 1;
 
 pub const BALL_RADIUS: f32 = 20.;
-pub const HEAVINESS_DURATION: Duration = Duration::new(5, 0);
+/// Balls falling below this height are eliminated from the round
+pub const ELIMINATION_HEIGHT: f32 = -600.;
+/// Time spent showing the results of a match before going back to the lobby
+pub const RESULTS_DURATION: Duration = Duration::new(5, 0);
 
-pub const WALL_TRANSFORM: Transform = Transform::from_xyz(0., -200., 0.);
-pub const WALL_HALF_WIDTH: f32 = 300.;
-pub const WALL_HALF_HEIGHT: f32 = 5.;
-
-#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, States)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, States, Serialize, Deserialize)]
 pub enum GameState {
     #[default]
     Lobby,
     InGame,
+    Results,
 }
 
 #[derive(Debug, PartialEq, Eq, Resource)]
@@ -50,23 +60,187 @@ pub struct Lobby {
     players: HashMap<u64, PlayerData>,
 }
 
+impl Lobby {
+    /// Players taking part in the matches, leaving out the spectators
+    fn playing(&self) -> impl Iterator<Item = (&u64, &PlayerData)> {
+        self.players.iter().filter(|(_, data)| !data.spectator)
+    }
+
+    /// Counts the death of the victim, and the kill of the killer unless they knocked themselves
+    fn record_elimination(&mut self, victim: u64, killer: Option<u64>) {
+        if let Some(data) = self.players.get_mut(&victim) {
+            data.stats.deaths += 1;
+        }
+        if let Some(data) = killer
+            .filter(|&killer| killer != victim)
+            .and_then(|killer| self.players.get_mut(&killer))
+        {
+            data.stats.kills += 1;
+        }
+    }
+}
+
+/// Index of a player among the local players of a client, the first one being 0
+pub type PlayerSlot = u8;
+
+/// Slot of the first local player of a client, whose id is the one of the client
+pub const FIRST_SLOT: PlayerSlot = 0;
+/// Players a single client can play with on the same machine
+pub const MAX_LOCAL_PLAYERS: usize = 4;
+/// Client ids are made of the time the clients connected at in milliseconds, leaving the high
+/// bits free for the slots
+const SLOT_SHIFT: u32 = 56;
+
+/// Id of a local player of a client, the first local player having the id of the client
+pub fn player_id(client_id: u64, slot: PlayerSlot) -> u64 {
+    client_id | ((slot as u64) << SLOT_SHIFT)
+}
+
+/// Client whose local player the player is
+pub fn client_of(player_id: u64) -> u64 {
+    player_id & ((1 << SLOT_SHIFT) - 1)
+}
+
+pub fn slot_of(player_id: u64) -> PlayerSlot {
+    (player_id >> SLOT_SHIFT) as PlayerSlot
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PlayerData {
     spawning_location: Vec3,
     entity: Option<Entity>,
+    profile: Profile,
+    /// Whether the player is ready for the next match, in the lobby
+    ready: bool,
+    /// Team chosen by the player, or given when the match starts to the players without one
+    team: Option<u8>,
+    stats: PlayerStats,
+    /// Whether the player only watches the matches, without a ball
+    spectator: bool,
+}
+
+/// Statistics of a player since joining the room, or during a match in the results
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    wins: u32,
+    kills: u32,
+    deaths: u32,
+}
+
+impl PlayerStats {
+    /// Statistics gained since the `earlier` ones were taken
+    fn since(self, earlier: PlayerStats) -> PlayerStats {
+        PlayerStats {
+            wins: self.wins.saturating_sub(earlier.wins),
+            kills: self.kills.saturating_sub(earlier.kills),
+            deaths: self.deaths.saturating_sub(earlier.deaths),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Mode {
+    #[default]
+    Classic,
+    /// Balls can hang to the platforms with a rope
+    Grapple,
+    /// Balls shoot arrows at each other
+    Arrows,
+    /// Balls hold the capture zone of the map to score
+    Capture,
+    /// Two teams push a shared ball into the goal of the other team
+    Football,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 5] = [
+        Mode::Classic,
+        Mode::Grapple,
+        Mode::Arrows,
+        Mode::Capture,
+        Mode::Football,
+    ];
+}
+
+/// Rules a room plays with, chosen when the room is created
+#[derive(Clone, Debug, Serialize, Deserialize, Resource)]
+pub struct Ruleset {
+    pub mode: Mode,
+    pub teams: Teams,
+    /// Whether the balls of a same team collide with each other
+    pub team_collisions: bool,
+    /// Whether the arrows eliminate the balls they hit instead of knocking them back
+    pub lethal_arrows: bool,
+    /// Seconds a player or team has to hold the capture zone to win
+    pub capture_time: u32,
+    /// Goals a team has to score to win in the football mode
+    pub goals_to_win: u32,
+    /// Seconds a ball can stay heavy with a full stamina
+    pub stamina_duration: f32,
+    /// Seconds an empty stamina takes to fully recover
+    pub stamina_recovery: f32,
+    /// Stamina a ball has to recover before becoming heavy again once it ran out, out of 1
+    pub exhaustion_threshold: f32,
+    /// Factor applied to the mass of the balls while heavy
+    pub heavy_mass_factor: f32,
+    /// Factor applied to the knockback of the balls hitting each other, none at 0
+    pub collision_knockback: f32,
+    /// Number of players needed to start a match
+    pub min_players: usize,
+    pub max_players: usize,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self {
+            mode: Mode::default(),
+            teams: Teams::default(),
+            team_collisions: true,
+            lethal_arrows: false,
+            capture_time: 30,
+            goals_to_win: 3,
+            stamina_duration: 5.,
+            stamina_recovery: 5.,
+            exhaustion_threshold: 0.3,
+            heavy_mass_factor: 4.,
+            collision_knockback: 1.,
+            min_players: 2,
+            max_players: 8,
+        }
+    }
+}
+
+/// Outcome of the last match, shown on the results screen
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Resource)]
+pub struct MatchResults {
+    winner: Option<u64>,
+    winning_team: Option<u8>,
+    /// Rounds won by each team since the teams were last changed
+    team_scores: Vec<u32>,
+    /// Players from first to last, with their statistics during the match
+    rankings: Vec<(u64, PlayerStats)>,
 }
 
 #[derive(Copy, Clone, Component, Debug, Default, Serialize, Deserialize)]
 pub struct PlayerInput {
     direction: Vec2,
+    /// Direction the player aims at with the mouse or the aiming keys, relative to the ball
+    aim: Vec2,
+    /// Whether the action key is held, its effect depends on the mode
+    action: bool,
 }
 
 #[derive(Copy, Clone, Component, Debug, Default, Serialize, Deserialize, Mul)]
 pub struct DirectionVector(Vec2);
 
 impl From<PlayerInput> for DirectionVector {
+    /// Keeps the magnitude of the analog sticks, the diagonals of the keys being brought back
+    /// to a unit length, and ignores the directions that are not finite
     fn from(value: PlayerInput) -> Self {
-        DirectionVector(value.direction.normalize_or_zero())
+        let direction = Some(value.direction)
+            .filter(|direction| direction.is_finite())
+            .map_or(Vec2::ZERO, |direction| direction.clamp_length_max(1.));
+        DirectionVector(direction)
     }
 }
 
@@ -82,10 +256,57 @@ impl From<DirectionVector> for Vec2 {
     }
 }
 
-#[derive(Component, Debug, Default)]
+/// State of a ball: translation, direction and stamina
+pub type EntitySnapshot = (Vec3, DirectionVector, Heavy);
+
+/// Entities sent each tick on the `ServerChannel::NetworkedEntities` channel
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct NetworkedEntities {
+    /// Balls of the players, by player
+    balls: HashMap<u64, EntitySnapshot>,
+    /// Other bodies, by entity on the server
+    bodies: HashMap<u64, BodySnapshot>,
+    /// Serialized state of the mode being played, if it has one
+    mode_state: Option<(Mode, Vec<u8>)>,
+    /// Collisions between balls since the last tick
+    collisions: Vec<BallCollision>,
+}
+
+/// Kind of the physics bodies replicated to the clients besides the balls of the players
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BodyKind {
+    Arrow,
+    Football,
+}
+
+/// Marks the bodies the server replicates to the clients, which draw them by kind
+#[derive(Component, Debug)]
+pub struct ReplicatedBody(pub BodyKind);
+
+/// State of a replicated body: kind, translation and velocity
+pub type BodySnapshot = (BodyKind, Vec2, Vec2);
+
+#[derive(Clone, Copy, Component, Debug, Serialize, Deserialize)]
 pub struct Heavy {
+    /// Whether the player holds the heavy key
+    pub holding: bool,
+    /// Whether the ball is actually heavy, which it can't be while exhausted
     pub heaviness: bool,
-    pub heavy_timer: Stopwatch,
+    /// Stamina left, between 0 and 1, spent while heavy and recovered otherwise
+    pub stamina: f32,
+    /// Set once the stamina runs out, until it recovers up to the `exhaustion_threshold`
+    pub exhausted: bool,
+}
+
+impl Default for Heavy {
+    fn default() -> Self {
+        Self {
+            holding: false,
+            heaviness: false,
+            stamina: 1.,
+            exhausted: false,
+        }
+    }
 }
 
 #[derive(Event)]
@@ -100,6 +321,24 @@ pub struct HeavinessReceivedEvent {
     heaviness: bool,
 }
 
+/// Sent by the server when the ball of a player has been eliminated from the round
+#[derive(Event)]
+pub struct EliminationEvent {
+    victim: u64,
+    /// Player who last hit the ball of the victim, if recently enough to be credited
+    killer: Option<u64>,
+}
+
+/// Sent when two balls hit each other, by the server and by the clients once replicated
+#[derive(Clone, Copy, Debug, Event, Serialize, Deserialize)]
+pub struct BallCollision {
+    /// Players whose balls collided
+    pub players: (u64, u64),
+    pub position: Vec2,
+    /// Magnitude of the knockback impulse given to the balls
+    pub impulse: f32,
+}
+
 pub fn connection_config() -> ConnectionConfig {
     ConnectionConfig {
         available_bytes_per_tick: 1024 * 1024,
@@ -119,3 +358,38 @@ struct Sending;
 
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
 struct Displaying;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_ids_round_trip() {
+        let client_id = 1_700_000_000_000;
+        for slot in 0..MAX_LOCAL_PLAYERS as PlayerSlot {
+            let id = player_id(client_id, slot);
+            assert_eq!(client_of(id), client_id);
+            assert_eq!(slot_of(id), slot);
+        }
+        assert_eq!(player_id(client_id, FIRST_SLOT), client_id);
+    }
+
+    #[test]
+    fn stats_since_earlier_ones() {
+        let earlier = PlayerStats {
+            wins: 2,
+            kills: 5,
+            deaths: 3,
+        };
+        let now = PlayerStats {
+            wins: 3,
+            kills: 5,
+            deaths: 7,
+        };
+        let gained = now.since(earlier);
+        assert_eq!((gained.wins, gained.kills, gained.deaths), (1, 0, 4));
+        // Statistics reset in between do not underflow
+        let gained = earlier.since(now);
+        assert_eq!((gained.wins, gained.kills, gained.deaths), (0, 0, 0));
+    }
+}
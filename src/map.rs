@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Layout of the level a game is played on, sent to the clients when a game starts
+#[derive(Clone, Debug, Serialize, Deserialize, Resource)]
+pub struct Map {
+    pub name: String,
+    pub platforms: Vec<Platform>,
+    /// Locations the balls are spawned at, handed out to the players in order
+    pub spawn_points: Vec<Vec2>,
+    /// Locations of the balls of each team, the teams past the listed ones use `spawn_points`
+    pub team_spawn_points: Vec<Vec<Vec2>>,
+    /// Zone to hold in the capture mode, the map cannot be played in this mode without one
+    pub capture_zone: Option<Zone>,
+    /// Layout of the football mode, the map cannot be played in this mode without one
+    pub pitch: Option<Pitch>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Platform {
+    pub position: Vec2,
+    pub half_size: Vec2,
+    /// Whether grapples can attach to the platform
+    pub grappleable: bool,
+}
+
+/// Where the shared ball of the football mode is put and which goals it must go in
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pitch {
+    pub kickoff: Vec2,
+    /// Goal defended by each team
+    pub goals: Vec<Zone>,
+}
+
+/// Rectangular region of a map the balls can go through
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Zone {
+    pub position: Vec2,
+    pub half_size: Vec2,
+}
+
+impl Map {
+    /// Maps shipped with the game, the first one being the default
+    pub fn builtin() -> Vec<Map> {
+        vec![
+            Map {
+                name: "Classic".to_owned(),
+                platforms: vec![Platform {
+                    position: Vec2::new(0., -200.),
+                    half_size: Vec2::new(300., 5.),
+                    grappleable: true,
+                }],
+                spawn_points: vec![
+                    Vec2::new(-100., 0.),
+                    Vec2::new(100., 0.),
+                    Vec2::new(-200., 0.),
+                    Vec2::new(200., 0.),
+                ],
+                team_spawn_points: vec![
+                    vec![Vec2::new(-250., 0.), Vec2::new(-200., 0.)],
+                    vec![Vec2::new(250., 0.), Vec2::new(200., 0.)],
+                    vec![Vec2::new(-100., 0.), Vec2::new(-50., 0.)],
+                    vec![Vec2::new(100., 0.), Vec2::new(50., 0.)],
+                ],
+                capture_zone: Some(Zone {
+                    position: Vec2::new(0., -145.),
+                    half_size: Vec2::new(60., 50.),
+                }),
+                pitch: None,
+            },
+            Map {
+                name: "Islands".to_owned(),
+                platforms: vec![
+                    Platform {
+                        position: Vec2::new(-250., -150.),
+                        half_size: Vec2::new(150., 5.),
+                        grappleable: true,
+                    },
+                    Platform {
+                        position: Vec2::new(250., -150.),
+                        half_size: Vec2::new(150., 5.),
+                        grappleable: true,
+                    },
+                    Platform {
+                        position: Vec2::new(0., 50.),
+                        half_size: Vec2::new(60., 5.),
+                        grappleable: false,
+                    },
+                ],
+                spawn_points: vec![
+                    Vec2::new(-250., 0.),
+                    Vec2::new(250., 0.),
+                    Vec2::new(0., 150.),
+                    Vec2::new(-150., 0.),
+                    Vec2::new(150., 0.),
+                ],
+                team_spawn_points: vec![
+                    vec![Vec2::new(-300., 0.), Vec2::new(-200., 0.)],
+                    vec![Vec2::new(300., 0.), Vec2::new(200., 0.)],
+                    vec![Vec2::new(-30., 150.)],
+                    vec![Vec2::new(30., 150.)],
+                ],
+                // Above the small platform in the middle
+                capture_zone: Some(Zone {
+                    position: Vec2::new(0., 105.),
+                    half_size: Vec2::new(60., 50.),
+                }),
+                pitch: None,
+            },
+            // Made for the grapple mode, with a narrow floor and anchors above it
+            Map {
+                name: "Canopy".to_owned(),
+                platforms: vec![
+                    Platform {
+                        position: Vec2::new(0., -200.),
+                        half_size: Vec2::new(150., 5.),
+                        grappleable: false,
+                    },
+                    Platform {
+                        position: Vec2::new(-300., 250.),
+                        half_size: Vec2::new(80., 5.),
+                        grappleable: true,
+                    },
+                    Platform {
+                        position: Vec2::new(0., 300.),
+                        half_size: Vec2::new(80., 5.),
+                        grappleable: true,
+                    },
+                    Platform {
+                        position: Vec2::new(300., 250.),
+                        half_size: Vec2::new(80., 5.),
+                        grappleable: true,
+                    },
+                ],
+                spawn_points: vec![
+                    Vec2::new(-100., -100.),
+                    Vec2::new(100., -100.),
+                    Vec2::new(-50., -100.),
+                    Vec2::new(50., -100.),
+                ],
+                team_spawn_points: vec![
+                    vec![Vec2::new(-120., -100.), Vec2::new(-80., -100.)],
+                    vec![Vec2::new(120., -100.), Vec2::new(80., -100.)],
+                ],
+                capture_zone: None,
+                pitch: None,
+            },
+            // Closed by walls so that nobody falls, made for the football mode
+            Map {
+                name: "Stadium".to_owned(),
+                platforms: vec![
+                    Platform {
+                        position: Vec2::new(0., -250.),
+                        half_size: Vec2::new(500., 10.),
+                        grappleable: false,
+                    },
+                    Platform {
+                        position: Vec2::new(0., 250.),
+                        half_size: Vec2::new(500., 10.),
+                        grappleable: true,
+                    },
+                    Platform {
+                        position: Vec2::new(-500., 0.),
+                        half_size: Vec2::new(10., 260.),
+                        grappleable: false,
+                    },
+                    Platform {
+                        position: Vec2::new(500., 0.),
+                        half_size: Vec2::new(10., 260.),
+                        grappleable: false,
+                    },
+                    // Crossbars of the goals
+                    Platform {
+                        position: Vec2::new(-450., -130.),
+                        half_size: Vec2::new(40., 5.),
+                        grappleable: false,
+                    },
+                    Platform {
+                        position: Vec2::new(450., -130.),
+                        half_size: Vec2::new(40., 5.),
+                        grappleable: false,
+                    },
+                ],
+                spawn_points: vec![
+                    Vec2::new(-200., -150.),
+                    Vec2::new(200., -150.),
+                    Vec2::new(-100., -150.),
+                    Vec2::new(100., -150.),
+                ],
+                team_spawn_points: vec![
+                    vec![Vec2::new(-200., -150.), Vec2::new(-300., -150.)],
+                    vec![Vec2::new(200., -150.), Vec2::new(300., -150.)],
+                ],
+                capture_zone: Some(Zone {
+                    position: Vec2::new(0., -190.),
+                    half_size: Vec2::new(60., 50.),
+                }),
+                pitch: Some(Pitch {
+                    kickoff: Vec2::new(0., 0.),
+                    goals: vec![
+                        Zone {
+                            position: Vec2::new(-460., -190.),
+                            half_size: Vec2::new(30., 50.),
+                        },
+                        Zone {
+                            position: Vec2::new(460., -190.),
+                            half_size: Vec2::new(30., 50.),
+                        },
+                    ],
+                }),
+            },
+        ]
+    }
+
+    /// Spawn points of the team, or the shared ones for the players without a team
+    pub fn spawn_points_of(&self, team: Option<u8>) -> &[Vec2] {
+        team.and_then(|team| self.team_spawn_points.get(team as usize))
+            .filter(|points| !points.is_empty())
+            .unwrap_or(&self.spawn_points)
+    }
+
+    /// Smallest rectangle holding the platforms, spawn points and zones of the map
+    pub fn bounds(&self) -> Rect {
+        let zones = self
+            .capture_zone
+            .iter()
+            .chain(self.pitch.iter().flat_map(|pitch| pitch.goals.iter()))
+            .map(|zone| Rect::from_center_half_size(zone.position, zone.half_size));
+        let platforms = self
+            .platforms
+            .iter()
+            .map(|platform| Rect::from_center_half_size(platform.position, platform.half_size));
+        let points = self
+            .spawn_points
+            .iter()
+            .chain(self.team_spawn_points.iter().flatten())
+            .map(|&point| Rect::from_corners(point, point));
+        platforms
+            .chain(zones)
+            .chain(points)
+            .reduce(|bounds, rect| bounds.union(rect))
+            .unwrap_or_default()
+    }
+
+    pub fn find(name: &str) -> Option<Map> {
+        Self::builtin().into_iter().find(|map| map.name == name)
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::builtin().swap_remove(0)
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    map::Map, server::communication::broadcast_networked_entities, ApplicationSide, GameState,
+    Mode, Processing, Ruleset, Sending,
+};
+
+use self::{
+    arrows::ArrowsMode, capture::CaptureMode, classic::ClassicMode, football::FootballMode,
+    grapple::GrappleMode,
+};
+
+pub use self::{
+    arrows::ARROW_RADIUS,
+    capture::{CaptureProgress, Contender},
+    football::{FootballScore, FOOTBALL_RADIUS},
+    grapple::GrappleAnchors,
+};
+
+mod arrows;
+mod capture;
+mod classic;
+mod football;
+mod grapple;
+
+/// Registers the rules of every mode, in the rooms as on the clients
+pub(crate) struct GameModesPlugin;
+
+impl Plugin for GameModesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameModes::default())
+            .insert_resource(ModeStateSnapshot::default())
+            .add_event::<RoundEndEvent>()
+            .add_game_mode(ClassicMode)
+            .add_game_mode(GrappleMode)
+            .add_game_mode(ArrowsMode)
+            .add_game_mode(CaptureMode)
+            .add_game_mode(FootballMode);
+    }
+}
+
+/// Rules of a game mode, the default methods being the rules of the classic mode
+pub trait GameMode: Send + Sync + 'static {
+    fn mode(&self) -> Mode;
+
+    /// Adds the systems of the mode, the systems simulating it should only run `in_mode`
+    fn build(&self, _app: &mut App) {}
+
+    /// Checks whether the mode can be played on the map with the ruleset
+    fn check(&self, _ruleset: &Ruleset, _map: &Map) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Location of the ball of the `index`th player of the team, or of the players without a team
+    fn spawn_location(&self, map: &Map, team: Option<u8>, index: usize) -> Vec2 {
+        let points = map.spawn_points_of(team);
+        // Players share spawn points when there are more players than points
+        points[index % points.len()]
+    }
+
+    /// Adds the components the mode needs to a ball spawned on the server
+    fn equip_ball(&self, _ball: &mut EntityCommands) {}
+
+    /// What happens to the balls falling off the map
+    fn fall_outcome(&self) -> FallOutcome {
+        FallOutcome::Eliminate
+    }
+
+    /// Whether the round ends once a single player or team is left, the mode may also end it
+    /// by sending a `RoundEndEvent`
+    fn last_standing_wins(&self) -> bool {
+        true
+    }
+
+    /// Players a match needs, a lone player winning right away when the last one standing wins
+    fn min_players(&self) -> usize {
+        if self.last_standing_wins() {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// What the action key does in the mode, none when it does nothing
+    fn action(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FallOutcome {
+    Eliminate,
+    /// The ball is put back at its spawning location
+    Respawn,
+}
+
+/// Rules of the registered modes
+#[derive(Default, Resource)]
+pub struct GameModes(HashMap<Mode, Box<dyn GameMode>>);
+
+impl GameModes {
+    /// Registry of the built-in modes, for checking rulesets outside of the worlds simulating them
+    pub fn builtin() -> GameModes {
+        let mut app = App::new();
+        app.add_plugins(GameModesPlugin);
+        app.world
+            .remove_resource::<GameModes>()
+            .expect("The modes plugin registers the modes")
+    }
+
+    /// Rules of the mode, the classic ones when the mode has not been registered
+    pub fn get(&self, mode: Mode) -> &dyn GameMode {
+        self.0
+            .get(&mode)
+            .or_else(|| self.0.get(&Mode::Classic))
+            .expect("The classic mode is always registered")
+            .as_ref()
+    }
+}
+
+/// Sent by the modes to end the round with the given winner
+#[derive(Event)]
+pub struct RoundEndEvent {
+    pub winner: Option<u64>,
+    pub winning_team: Option<u8>,
+}
+
+/// Serialized state of the active mode, sent along with the entities
+#[derive(Debug, Default, Resource)]
+pub(crate) struct ModeStateSnapshot(pub(crate) Option<(Mode, Vec<u8>)>);
+
+pub trait AppGameModeExt {
+    fn add_game_mode(&mut self, mode: impl GameMode) -> &mut Self;
+
+    /// Sends the state of the mode from the rooms to the clients each tick, and removes it on
+    /// both sides once the match is over
+    fn replicate_mode_state<T: Resource + Serialize + DeserializeOwned>(
+        &mut self,
+        mode: Mode,
+    ) -> &mut Self;
+}
+
+impl AppGameModeExt for App {
+    fn add_game_mode(&mut self, mode: impl GameMode) -> &mut Self {
+        mode.build(self);
+        self.world
+            .resource_mut::<GameModes>()
+            .0
+            .insert(mode.mode(), Box::new(mode));
+        self
+    }
+
+    fn replicate_mode_state<T: Resource + Serialize + DeserializeOwned>(
+        &mut self,
+        mode: Mode,
+    ) -> &mut Self {
+        self.add_systems(
+            FixedUpdate,
+            (
+                write_mode_state::<T>(mode)
+                    .in_set(Sending)
+                    .before(broadcast_networked_entities)
+                    .run_if(resource_equals(ApplicationSide::Server))
+                    .run_if(resource_exists::<T>()),
+                read_mode_state::<T>(mode)
+                    .in_set(Processing)
+                    .run_if(resource_equals(ApplicationSide::Client)),
+            )
+                .run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(OnExit(GameState::InGame), remove_mode_state::<T>)
+    }
+}
+
+/// Run condition of the systems simulating the mode, only true in the rooms playing it, the
+/// clients being sent the state of the mode instead
+pub fn in_mode(
+    mode: Mode,
+) -> impl FnMut(Res<ApplicationSide>, Option<Res<Ruleset>>) -> bool + Clone {
+    move |side, ruleset| {
+        *side == ApplicationSide::Server && ruleset.map_or(false, |ruleset| ruleset.mode == mode)
+    }
+}
+
+fn write_mode_state<T: Resource + Serialize>(
+    mode: Mode,
+) -> impl FnMut(Res<T>, ResMut<ModeStateSnapshot>) {
+    move |state, mut snapshot| {
+        snapshot.0 = Some((mode, bincode::serialize(&*state).unwrap()));
+    }
+}
+
+fn read_mode_state<T: Resource + DeserializeOwned>(
+    mode: Mode,
+) -> impl FnMut(Commands, Res<ModeStateSnapshot>) {
+    move |mut commands, snapshot| {
+        if !snapshot.is_changed() {
+            return;
+        }
+        let Some((snapshot_mode, bytes)) = &snapshot.0 else {
+            return;
+        };
+        if *snapshot_mode != mode {
+            return;
+        }
+        if let Ok(state) = bincode::deserialize::<T>(bytes) {
+            commands.insert_resource(state);
+        }
+    }
+}
+
+fn remove_mode_state<T: Resource>(mut commands: Commands) {
+    commands.remove_resource::<T>();
+}
@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::EntityCommands, prelude::*, time::Stopwatch};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    ball::{Ball, LastHit},
+    scene::Wall,
+    BodyKind, EliminationEvent, GameState, InputReceivedEvent, Lobby, Mode, Processing,
+    ReplicatedBody, Ruleset, BALL_RADIUS,
+};
+
+use super::{in_mode, GameMode};
+
+/// Charge time after which the arrows stop getting faster
+const FULL_CHARGE: Duration = Duration::from_millis(1500);
+/// Launch speeds of the arrows, from no charge to full charge, in pixels per second
+const MIN_ARROW_SPEED: f32 = 300.;
+const MAX_ARROW_SPEED: f32 = 1200.;
+const ARROW_LIFETIME: Duration = Duration::from_secs(3);
+pub const ARROW_RADIUS: f32 = 4.;
+/// Impulse given to a ball hit by an arrow, per pixel per second of the arrow speed
+const KNOCKBACK_FACTOR: f32 = 0.02;
+
+/// Lets the balls shoot arrows at each other, simulated by the server only
+pub struct ArrowsMode;
+
+impl GameMode for ArrowsMode {
+    fn mode(&self) -> Mode {
+        Mode::Arrows
+    }
+
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (aim_bows, charge_bows, hit_with_arrows, expire_arrows)
+                .chain()
+                .in_set(Processing)
+                .run_if(in_mode(Mode::Arrows)),
+        )
+        .add_systems(OnExit(GameState::InGame), despawn_arrows);
+    }
+
+    fn equip_ball(&self, ball: &mut EntityCommands) {
+        ball.insert(Bow::default());
+    }
+
+    fn action(&self) -> Option<&'static str> {
+        Some("Hold to charge an arrow, release to shoot")
+    }
+}
+
+#[derive(Component, Debug, Default)]
+struct Bow {
+    aim: Vec2,
+    /// Whether the player holds the action key
+    charging: bool,
+    charge: Stopwatch,
+}
+
+#[derive(Component, Debug)]
+struct Arrow {
+    /// Ball that shot the arrow, which the arrow cannot hit
+    shooter: Entity,
+    lifetime: Timer,
+}
+
+fn aim_bows(
+    mut query: Query<&mut Bow>,
+    lobby: Res<Lobby>,
+    mut event_reader: EventReader<InputReceivedEvent>,
+) {
+    for InputReceivedEvent { origin, input } in event_reader.iter() {
+        let Some(entity) = lobby.players.get(origin).and_then(|data| data.entity) else {
+            continue;
+        };
+        if let Ok(mut bow) = query.get_mut(entity) {
+            // Keeps the last aim when the player aims at the ball itself
+            if input.aim != Vec2::ZERO {
+                bow.aim = input.aim.normalize();
+            }
+            bow.charging = input.action;
+        }
+    }
+}
+
+/// Charges the bows while the action key is held, and shoots once it is released
+fn charge_bows(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, &mut Bow), With<Ball>>,
+    time: Res<Time>,
+) {
+    for (entity, transform, mut bow) in query.iter_mut() {
+        if bow.charging {
+            bow.charge.tick(time.delta());
+            continue;
+        }
+        if bow.charge.elapsed().is_zero() {
+            continue;
+        }
+        let charge = (bow.charge.elapsed().as_secs_f32() / FULL_CHARGE.as_secs_f32()).min(1.);
+        bow.charge.reset();
+        let aim = if bow.aim == Vec2::ZERO {
+            Vec2::Y
+        } else {
+            bow.aim
+        };
+        let speed = MIN_ARROW_SPEED + (MAX_ARROW_SPEED - MIN_ARROW_SPEED) * charge;
+        let position = transform.translation.truncate() + aim * (BALL_RADIUS + ARROW_RADIUS);
+        commands.spawn((
+            Arrow {
+                shooter: entity,
+                lifetime: Timer::new(ARROW_LIFETIME, TimerMode::Once),
+            },
+            ReplicatedBody(BodyKind::Arrow),
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.))),
+            RigidBody::Dynamic,
+            Collider::ball(ARROW_RADIUS),
+            // Hits are handled by hand rather than by the solver
+            Sensor,
+            ActiveEvents::COLLISION_EVENTS,
+            Velocity::linear(aim * speed),
+            GravityScale(1.),
+            Ccd::enabled(),
+        ));
+    }
+}
+
+/// Knocks back or eliminates the balls hit by the arrows, depending on the ruleset, and removes
+/// the arrows hitting a ball or a wall
+fn hit_with_arrows(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    arrows: Query<(&Arrow, &Velocity)>,
+    mut balls: Query<(&mut ExternalImpulse, &mut LastHit), With<Ball>>,
+    walls: Query<(), With<Wall>>,
+    ruleset: Res<Ruleset>,
+    mut lobby: ResMut<Lobby>,
+    time: Res<Time>,
+    mut elimination_writer: EventWriter<EliminationEvent>,
+) {
+    let mut spent = Vec::new();
+    for event in collision_events.iter() {
+        let CollisionEvent::Started(first, second, _) = *event else {
+            continue;
+        };
+        let (arrow_entity, other) = if arrows.contains(first) {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let Ok((arrow, velocity)) = arrows.get(arrow_entity) else {
+            continue;
+        };
+        if other == arrow.shooter || spent.contains(&arrow_entity) {
+            continue;
+        }
+        if let Ok((mut impulse, mut last_hit)) = balls.get_mut(other) {
+            let shooter = lobby
+                .players
+                .iter()
+                .find(|(_, data)| data.entity == Some(arrow.shooter))
+                .map(|(&id, _)| id);
+            if ruleset.lethal_arrows {
+                let victim = lobby
+                    .players
+                    .iter_mut()
+                    .find(|(_, data)| data.entity == Some(other));
+                if let Some((&victim, data)) = victim {
+                    commands.entity(other).despawn_recursive();
+                    data.entity = None;
+                    elimination_writer.send(EliminationEvent {
+                        victim,
+                        killer: shooter,
+                    });
+                }
+            } else {
+                impulse.impulse += velocity.linvel * KNOCKBACK_FACTOR;
+                if let Some(shooter) = shooter {
+                    last_hit.record(shooter, &time);
+                }
+            }
+        } else if !walls.contains(other) {
+            // Arrows go through each other
+            continue;
+        }
+        commands.entity(arrow_entity).despawn();
+        spent.push(arrow_entity);
+    }
+}
+
+fn expire_arrows(mut commands: Commands, mut query: Query<(Entity, &mut Arrow)>, time: Res<Time>) {
+    for (entity, mut arrow) in query.iter_mut() {
+        if arrow.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn despawn_arrows(mut commands: Commands, arrows: Query<Entity, With<Arrow>>) {
+    for arrow in arrows.iter() {
+        commands.entity(arrow).despawn();
+    }
+}
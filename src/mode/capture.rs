@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{map::Map, scene::CaptureZone, GameState, Lobby, Mode, Processing, Ruleset};
+
+use super::{in_mode, AppGameModeExt, GameMode, RoundEndEvent};
+
+/// Scores the players holding the capture zone of the map
+pub struct CaptureMode;
+
+impl GameMode for CaptureMode {
+    fn mode(&self) -> Mode {
+        Mode::Capture
+    }
+
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            reset_capture_progress.run_if(in_mode(Mode::Capture)),
+        )
+        .add_systems(
+            FixedUpdate,
+            update_capture_progress
+                .in_set(Processing)
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_mode(Mode::Capture))
+                .run_if(resource_exists::<CaptureProgress>()),
+        )
+        .replicate_mode_state::<CaptureProgress>(Mode::Capture);
+    }
+
+    fn check(&self, ruleset: &Ruleset, map: &Map) -> Result<(), String> {
+        if ruleset.capture_time == 0 {
+            return Err("Invalid capture time".to_owned());
+        }
+        if map.capture_zone.is_none() {
+            return Err(format!("The map {} has no capture zone", map.name));
+        }
+        Ok(())
+    }
+}
+
+/// Player, or team in the team modes, competing for the zone
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Contender {
+    Player(u64),
+    Team(u8),
+}
+
+/// Progress of the capture mode, sent to the clients
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Resource)]
+pub struct CaptureProgress {
+    /// Seconds each contender has held the zone alone
+    pub scores: HashMap<Contender, f32>,
+    /// Contender alone in the zone
+    pub holder: Option<Contender>,
+    /// Whether several contenders are in the zone, which freezes their scores
+    pub contested: bool,
+}
+
+fn reset_capture_progress(mut commands: Commands) {
+    commands.insert_resource(CaptureProgress::default());
+}
+
+fn update_capture_progress(
+    mut progress: ResMut<CaptureProgress>,
+    rapier_context: Res<RapierContext>,
+    zones: Query<Entity, With<CaptureZone>>,
+    lobby: Res<Lobby>,
+    ruleset: Res<Ruleset>,
+    time: Res<Time>,
+    mut round_end_writer: EventWriter<RoundEndEvent>,
+) {
+    let Ok(zone) = zones.get_single() else {
+        return;
+    };
+    let mut contenders = lobby.players.iter().filter_map(|(&id, data)| {
+        let ball = data.entity?;
+        rapier_context
+            .intersection_pair(zone, ball)
+            .filter(|&intersecting| intersecting)?;
+        // Players only have a team in the team modes once the match has started
+        Some(match data.team {
+            Some(team) => Contender::Team(team),
+            None => Contender::Player(id),
+        })
+    });
+    let first = contenders.next();
+    let contested = first.is_some() && contenders.any(|other| Some(other) != first);
+    let holder = first.filter(|_| !contested);
+    progress.holder = holder;
+    progress.contested = contested;
+    let Some(holder) = holder else {
+        return;
+    };
+    let score = progress.scores.entry(holder).or_default();
+    *score += time.delta_seconds();
+    if *score < ruleset.capture_time as f32 {
+        return;
+    }
+    let (winner, winning_team) = match holder {
+        Contender::Player(id) => (Some(id), None),
+        Contender::Team(team) => (None, Some(team)),
+    };
+    round_end_writer.send(RoundEndEvent {
+        winner,
+        winning_team,
+    });
+}
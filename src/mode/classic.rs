@@ -0,0 +1,12 @@
+use crate::Mode;
+
+use super::GameMode;
+
+/// Last ball standing on the map wins, with no other rules
+pub struct ClassicMode;
+
+impl GameMode for ClassicMode {
+    fn mode(&self) -> Mode {
+        Mode::Classic
+    }
+}
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ball::Ball, map::Map, scene::Goal, BodyKind, GameState, Lobby, Mode, Processing,
+    ReplicatedBody, Ruleset, Teams, ELIMINATION_HEIGHT,
+};
+
+use super::{in_mode, AppGameModeExt, FallOutcome, GameMode, RoundEndEvent};
+
+pub const FOOTBALL_RADIUS: f32 = 35.;
+
+/// Lets two teams push a shared ball into the goals of the map
+pub struct FootballMode;
+
+impl GameMode for FootballMode {
+    fn mode(&self) -> Mode {
+        Mode::Football
+    }
+
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::InGame),
+            spawn_football.run_if(in_mode(Mode::Football)),
+        )
+        .add_systems(
+            FixedUpdate,
+            score_goals
+                .in_set(Processing)
+                .run_if(in_state(GameState::InGame))
+                .run_if(in_mode(Mode::Football))
+                .run_if(resource_exists::<FootballScore>()),
+        )
+        .add_systems(OnExit(GameState::InGame), despawn_football)
+        .replicate_mode_state::<FootballScore>(Mode::Football);
+    }
+
+    fn check(&self, ruleset: &Ruleset, map: &Map) -> Result<(), String> {
+        if ruleset.goals_to_win == 0 {
+            return Err("Invalid number of goals".to_owned());
+        }
+        if ruleset.teams != Teams::Two {
+            return Err("The football mode is played by two teams".to_owned());
+        }
+        if map
+            .pitch
+            .as_ref()
+            .map_or(true, |pitch| pitch.goals.len() < 2)
+        {
+            return Err(format!("The map {} has no goals", map.name));
+        }
+        Ok(())
+    }
+
+    fn fall_outcome(&self) -> FallOutcome {
+        FallOutcome::Respawn
+    }
+}
+
+/// Goals scored by each team, sent to the clients
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Resource)]
+pub struct FootballScore {
+    pub goals: Vec<u32>,
+}
+
+#[derive(Component)]
+struct Football;
+
+fn spawn_football(mut commands: Commands, map: Res<Map>, ruleset: Res<Ruleset>) {
+    let Some(pitch) = &map.pitch else {
+        return;
+    };
+    commands.insert_resource(FootballScore {
+        goals: vec![0; ruleset.teams.count() as usize],
+    });
+    commands.spawn((
+        Football,
+        ReplicatedBody(BodyKind::Football),
+        TransformBundle::from_transform(Transform::from_translation(pitch.kickoff.extend(0.))),
+        RigidBody::Dynamic,
+        Collider::ball(FOOTBALL_RADIUS),
+        // Lighter than the balls of the players so that they can push it around
+        ColliderMassProperties::Density(0.2),
+        Restitution::coefficient(0.8),
+        GravityScale(2.),
+        Velocity::zero(),
+        Ccd::enabled(),
+    ));
+}
+
+/// Counts the goals, and puts the football and the balls back in place after each goal or
+/// when the football falls off the map
+fn score_goals(
+    rapier_context: Res<RapierContext>,
+    mut football: Query<(Entity, &mut Transform, &mut Velocity), With<Football>>,
+    mut balls: Query<(&mut Transform, &mut Velocity), (With<Ball>, Without<Football>)>,
+    goals: Query<(Entity, &Goal)>,
+    map: Res<Map>,
+    lobby: Res<Lobby>,
+    ruleset: Res<Ruleset>,
+    mut score: ResMut<FootballScore>,
+    mut round_end_writer: EventWriter<RoundEndEvent>,
+) {
+    let (Ok((entity, mut transform, mut velocity)), Some(pitch)) =
+        (football.get_single_mut(), &map.pitch)
+    else {
+        return;
+    };
+    let defending_team = goals
+        .iter()
+        .find(|&(goal, _)| rapier_context.intersection_pair(goal, entity) == Some(true))
+        .map(|(_, goal)| goal.team);
+    if let Some(defending_team) = defending_team {
+        // Only played by two teams, so the other team scores
+        let scoring_team = if defending_team == 0 { 1 } else { 0 };
+        let Some(goals) = score.goals.get_mut(scoring_team as usize) else {
+            return;
+        };
+        *goals += 1;
+        if *goals >= ruleset.goals_to_win {
+            round_end_writer.send(RoundEndEvent {
+                winner: None,
+                winning_team: Some(scoring_team),
+            });
+            return;
+        }
+    } else if transform.translation.y > ELIMINATION_HEIGHT {
+        return;
+    }
+    transform.translation = pitch.kickoff.extend(0.);
+    *velocity = Velocity::zero();
+    for data in lobby.players.values() {
+        let Some(Ok((mut transform, mut velocity))) =
+            data.entity.map(|entity| balls.get_mut(entity))
+        else {
+            continue;
+        };
+        transform.translation = data.spawning_location;
+        *velocity = Velocity::zero();
+    }
+}
+
+fn despawn_football(mut commands: Commands, football: Query<Entity, With<Football>>) {
+    for entity in football.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
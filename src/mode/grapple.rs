@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{ball::Ball, scene::Wall, InputReceivedEvent, Lobby, Mode, Processing};
+
+use super::{in_mode, AppGameModeExt, GameMode};
+
+/// Longest distance a grapple can reach, in pixels
+const GRAPPLE_RANGE: f32 = 400.;
+
+/// Lets the balls hang to the platforms with a rope, simulated by the server only
+pub struct GrappleMode;
+
+impl GameMode for GrappleMode {
+    fn mode(&self) -> Mode {
+        Mode::Grapple
+    }
+
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (aim_grapples, update_grapples, collect_anchors)
+                .chain()
+                .in_set(Processing)
+                .run_if(in_mode(Mode::Grapple)),
+        )
+        .replicate_mode_state::<GrappleAnchors>(Mode::Grapple);
+    }
+
+    fn equip_ball(&self, ball: &mut EntityCommands) {
+        ball.insert(Grapple::default());
+    }
+
+    fn action(&self) -> Option<&'static str> {
+        Some("Grapple")
+    }
+}
+
+#[derive(Component, Debug, Default)]
+struct Grapple {
+    /// Direction the player aims at while holding the action key
+    aim: Option<Vec2>,
+    /// Point the rope is attached to
+    anchor: Option<Vec2>,
+}
+
+/// Points the ropes of the players are attached to, sent to the clients to draw the ropes
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Resource)]
+pub struct GrappleAnchors(pub HashMap<u64, Vec2>);
+
+fn aim_grapples(
+    mut query: Query<&mut Grapple>,
+    lobby: Res<Lobby>,
+    mut event_reader: EventReader<InputReceivedEvent>,
+) {
+    for InputReceivedEvent { origin, input } in event_reader.iter() {
+        let Some(entity) = lobby.players.get(origin).and_then(|data| data.entity) else {
+            continue;
+        };
+        if let Ok(mut grapple) = query.get_mut(entity) {
+            grapple.aim = input.action.then(|| input.aim.normalize_or_zero());
+        }
+    }
+}
+
+/// Attaches a rope to the aimed platform when the action key is pressed, and releases it once
+/// the key is up
+fn update_grapples(
+    mut commands: Commands,
+    rapier_context: Res<RapierContext>,
+    mut balls: Query<(Entity, &Transform, &mut Grapple), With<Ball>>,
+    walls: Query<(&Wall, &Transform)>,
+) {
+    for (entity, transform, mut grapple) in balls.iter_mut() {
+        match (grapple.aim, grapple.anchor) {
+            (None, Some(_)) => {
+                commands.entity(entity).remove::<ImpulseJoint>();
+                grapple.anchor = None;
+            }
+            (Some(aim), None) if aim != Vec2::ZERO => {
+                let origin = transform.translation.truncate();
+                let grappleable =
+                    |wall: Entity| walls.get(wall).map_or(false, |(wall, _)| wall.grappleable);
+                let filter = QueryFilter::default()
+                    .exclude_rigid_body(entity)
+                    .predicate(&grappleable);
+                let Some((wall, distance)) =
+                    rapier_context.cast_ray(origin, aim, GRAPPLE_RANGE, true, filter)
+                else {
+                    continue;
+                };
+                let Ok((_, wall_transform)) = walls.get(wall) else {
+                    continue;
+                };
+                let anchor = origin + aim * distance;
+                let rope = RopeJointBuilder::new()
+                    .local_anchor1(anchor - wall_transform.translation.truncate())
+                    .local_anchor2(Vec2::ZERO)
+                    .limits([0., distance]);
+                commands
+                    .entity(entity)
+                    .insert(ImpulseJoint::new(wall, rope));
+                grapple.anchor = Some(anchor);
+            }
+            _ => (),
+        }
+    }
+}
+
+fn collect_anchors(mut commands: Commands, lobby: Res<Lobby>, grapples: Query<&Grapple>) {
+    let anchors = lobby
+        .players
+        .iter()
+        .filter_map(|(&id, data)| {
+            let anchor = grapples.get(data.entity?).ok()?.anchor?;
+            Some((id, anchor))
+        })
+        .collect();
+    commands.insert_resource(GrappleAnchors(anchors));
+}
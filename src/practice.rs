@@ -0,0 +1,140 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_rapier2d::prelude::{NoUserData, RapierConfiguration, RapierPhysicsPlugin, TimestepMode};
+
+use crate::{
+    ball::BallsPlugin,
+    client::{
+        chat::ChatInput,
+        communication::first_player_input,
+        input::{ActionState, InputAction, InputActionsPlugin},
+        local::LocalPlayers,
+        mark_local_player, LocalClientId, LocalPlayer,
+    },
+    display::DisplayPlugin,
+    map::Map,
+    mode::GameModesPlugin,
+    player_id,
+    scene::GameScenePlugin,
+    ApplicationSide, EliminationEvent, GameState, HeavinessReceivedEvent, InputReceivedEvent,
+    Lobby, PlayerData, Processing, Receiving, Ruleset, FIRST_SLOT, FIXED_DT, PHYSICS_DT, PPM,
+    SUBSTEPS,
+};
+
+/// Id standing for the client in the offline practice, which no networked client can have since
+/// their ids are the time they connected at
+const PRACTICE_CLIENT_ID: u64 = 0;
+
+/// Plays alone on a map without any server, simulating the balls in the same process as the
+/// display, to practice the movement, try out maps or debug the physics
+pub struct PracticePlugin {
+    /// Name of the built-in map to play on, the default map if none
+    pub map: Option<String>,
+}
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        let map = match &self.map {
+            Some(name) => Map::find(name).unwrap_or_else(|| {
+                let names: Vec<String> = Map::builtin().into_iter().map(|map| map.name).collect();
+                panic!("Unknown map {name}, the maps are: {}", names.join(", "))
+            }),
+            None => Map::default(),
+        };
+        let lobby = Lobby {
+            players: [(
+                player_id(PRACTICE_CLIENT_ID, FIRST_SLOT),
+                PlayerData::default(),
+            )]
+            .into(),
+        };
+        // The balls are simulated here as they are in the rooms, the inputs being read locally
+        // instead of being received from the clients
+        app.add_state::<GameState>()
+            .insert_resource(lobby)
+            .insert_resource(map)
+            .insert_resource(Ruleset::default())
+            .insert_resource(LocalClientId(PRACTICE_CLIENT_ID))
+            .insert_resource(LocalPlayers::default())
+            .insert_resource(ChatInput::default())
+            .insert_resource(ApplicationSide::Server)
+            .add_event::<InputReceivedEvent>()
+            .add_event::<HeavinessReceivedEvent>()
+            .insert_resource(FixedTime::new_from_secs(FIXED_DT))
+            .insert_resource(RapierConfiguration {
+                timestep_mode: TimestepMode::Fixed {
+                    dt: PHYSICS_DT,
+                    substeps: SUBSTEPS,
+                },
+                ..default()
+            })
+            .add_plugins(DefaultPlugins.set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Practice".to_owned(),
+                    ..default()
+                }),
+                ..default()
+            }))
+            .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PPM))
+            .configure_sets(FixedUpdate, (Receiving, Processing).chain())
+            .configure_sets(OnEnter(GameState::InGame), (Receiving, Processing).chain())
+            .add_plugins((
+                InputActionsPlugin,
+                BallsPlugin,
+                GameScenePlugin,
+                DisplayPlugin,
+                GameModesPlugin,
+            ))
+            .add_systems(OnEnter(GameState::Lobby), start_round)
+            .add_systems(
+                OnEnter(GameState::InGame),
+                mark_local_player.after(Processing),
+            )
+            .add_systems(
+                FixedUpdate,
+                read_local_input
+                    .in_set(Receiving)
+                    .run_if(in_state(GameState::InGame)),
+            )
+            .add_systems(
+                Update,
+                restart_round_on_elimination.run_if(in_state(GameState::InGame)),
+            );
+    }
+}
+
+/// There is no lobby to wait in, the round starts right away
+fn start_round(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::InGame);
+}
+
+/// Hands the input of the local player to the balls as if it had been received from a client
+fn read_local_input(
+    actions: Res<ActionState>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    local_balls: Query<(&Transform, &LocalPlayer)>,
+    mut input_writer: EventWriter<InputReceivedEvent>,
+    mut heaviness_writer: EventWriter<HeavinessReceivedEvent>,
+) {
+    let origin = player_id(PRACTICE_CLIENT_ID, FIRST_SLOT);
+    input_writer.send(InputReceivedEvent {
+        origin,
+        input: first_player_input(&actions, &windows, &cameras, &local_balls),
+    });
+    heaviness_writer.send(HeavinessReceivedEvent {
+        origin,
+        heaviness: actions.pressed(InputAction::Heavy),
+    });
+}
+
+/// Going through the lobby respawns the scene and the ball of the player once it fell
+fn restart_round_on_elimination(
+    mut elimination_reader: EventReader<EliminationEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if elimination_reader.is_empty() {
+        return;
+    }
+    elimination_reader.clear();
+    next_state.set(GameState::Lobby);
+}
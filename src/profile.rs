@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const MAX_NAME_LENGTH: usize = 16;
+const DEFAULT_NAME: &str = "Player";
+
+/// Colors players can pick from, as many as there can be players in a room so that nobody has to share
+pub const PALETTE: [Color; 16] = [
+    Color::rgb(0.0, 0.38, 0.39),
+    Color::rgb(0.85, 0.33, 0.31),
+    Color::rgb(0.95, 0.69, 0.2),
+    Color::rgb(0.36, 0.72, 0.36),
+    Color::rgb(0.26, 0.55, 0.79),
+    Color::rgb(0.58, 0.4, 0.74),
+    Color::rgb(0.93, 0.47, 0.69),
+    Color::rgb(0.55, 0.34, 0.29),
+    Color::rgb(0.1, 0.75, 0.75),
+    Color::rgb(0.74, 0.74, 0.13),
+    Color::rgb(0.97, 0.5, 0.2),
+    Color::rgb(0.4, 0.45, 0.85),
+    Color::rgb(0.7, 0.2, 0.45),
+    Color::rgb(0.5, 0.8, 0.6),
+    Color::rgb(0.8, 0.6, 0.9),
+    Color::rgb(0.6, 0.6, 0.6),
+];
+
+/// Index of the color of a player in the `PALETTE`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerColor(pub u8);
+
+impl PlayerColor {
+    pub fn color(self) -> Color {
+        PALETTE.get(self.0 as usize).copied().unwrap_or(PALETTE[0])
+    }
+}
+
+/// How a player is shown to the others
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Resource)]
+pub struct Profile {
+    pub name: String,
+    pub color: PlayerColor,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_NAME.to_owned(),
+            color: PlayerColor::default(),
+        }
+    }
+}
+
+impl Profile {
+    /// Makes the profile valid and distinct from the profiles of the other players
+    pub fn sanitized<'a>(self, others: impl Iterator<Item = &'a Profile> + Clone) -> Self {
+        let name: String = self
+            .name
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(MAX_NAME_LENGTH)
+            .collect();
+        let name = match name.trim() {
+            "" => DEFAULT_NAME,
+            name => name,
+        };
+        let unique_name = (1..)
+            .map(|n| match n {
+                1 => name.to_owned(),
+                n => format!("{} {}", name, n),
+            })
+            .find(|candidate| !others.clone().any(|other| &other.name == candidate))
+            .unwrap();
+        let taken = |color: PlayerColor| others.clone().any(|other| other.color == color);
+        let color = if (self.color.0 as usize) < PALETTE.len() && !taken(self.color) {
+            self.color
+        } else {
+            (0..PALETTE.len() as u8)
+                .map(PlayerColor)
+                .find(|&color| !taken(color))
+                .unwrap_or_default()
+        };
+        Self {
+            name: unique_name,
+            color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, color: u8) -> Profile {
+        Profile {
+            name: name.to_owned(),
+            color: PlayerColor(color),
+        }
+    }
+
+    #[test]
+    fn names_are_cleaned_up() {
+        let sanitized = profile("\u{7}Bo\nb\t", 0).sanitized([].iter());
+        assert_eq!(sanitized.name, "Bob");
+        let long = "abcdefghijklmnopqrstuvwxyz";
+        let sanitized = profile(long, 0).sanitized([].iter());
+        assert_eq!(sanitized.name, &long[..MAX_NAME_LENGTH]);
+        let sanitized = profile(" \n ", 0).sanitized([].iter());
+        assert_eq!(sanitized.name, DEFAULT_NAME);
+    }
+
+    #[test]
+    fn taken_names_get_a_suffix() {
+        let others = [profile("Bob", 0), profile("Bob 2", 1)];
+        let sanitized = profile("Bob", 2).sanitized(others.iter());
+        assert_eq!(sanitized.name, "Bob 3");
+        let sanitized = profile("Alice", 2).sanitized(others.iter());
+        assert_eq!(sanitized.name, "Alice");
+    }
+
+    #[test]
+    fn taken_or_unknown_colors_are_replaced() {
+        let others = [profile("Alice", 0), profile("Bob", 1)];
+        assert_eq!(
+            profile("Eve", 1).sanitized(others.iter()).color,
+            PlayerColor(2)
+        );
+        assert_eq!(
+            profile("Eve", 5).sanitized(others.iter()).color,
+            PlayerColor(5)
+        );
+        let unknown = PALETTE.len() as u8;
+        assert_eq!(
+            profile("Eve", unknown).sanitized(others.iter()).color,
+            PlayerColor(2)
+        );
+    }
+}
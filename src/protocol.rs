@@ -0,0 +1,154 @@
+use std::{hash::Hasher, sync::OnceLock};
+
+use bevy_renet::renet::{transport::NETCODE_USER_DATA_BYTES, ChannelConfig, SendType};
+use serde_reflection::{Tracer, TracerConfig};
+
+use crate::{
+    client::ClientMessage,
+    connection_config,
+    mode::{CaptureProgress, FootballScore, GrappleAnchors},
+    server::{chat::ChatLine, ServerMessage},
+    NetworkedEntities, PlayerInput, PlayerSlot,
+};
+
+/// Version of the protocol, to bump whenever client and server stop understanding each other
+/// in a way the message layouts do not reflect
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Protocol id given to netcode, kept the same across versions so that the server can still
+/// tell outdated clients why they are rejected
+pub const NETCODE_PROTOCOL_ID: u64 = u64::from_le_bytes(*b"bong\0\0\0\0");
+
+/// Protocol spoken by one side of the connection, sent by the client in its user data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProtocolInfo {
+    pub version: u32,
+    /// Hash of the version along with the layout of the messages and channels
+    pub id: u64,
+}
+
+impl ProtocolInfo {
+    pub fn current() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            id: protocol_id(),
+        }
+    }
+
+    pub fn to_user_data(self) -> [u8; NETCODE_USER_DATA_BYTES] {
+        let mut user_data = [0; NETCODE_USER_DATA_BYTES];
+        user_data[..4].copy_from_slice(&self.version.to_le_bytes());
+        user_data[4..12].copy_from_slice(&self.id.to_le_bytes());
+        user_data
+    }
+
+    pub fn from_user_data(user_data: &[u8; NETCODE_USER_DATA_BYTES]) -> Self {
+        Self {
+            version: u32::from_le_bytes(user_data[..4].try_into().unwrap()),
+            id: u64::from_le_bytes(user_data[4..12].try_into().unwrap()),
+        }
+    }
+}
+
+/// Id of the protocol of this build, computed once from the version, messages and channels
+pub fn protocol_id() -> u64 {
+    static PROTOCOL_ID: OnceLock<u64> = OnceLock::new();
+    *PROTOCOL_ID.get_or_init(|| {
+        let mut hasher = Fnv1aHasher::default();
+        hasher.write(&PROTOCOL_VERSION.to_le_bytes());
+        hasher.write(&message_schemas());
+        let config = connection_config();
+        for channel in config
+            .server_channels_config
+            .iter()
+            .chain(config.client_channels_config.iter())
+        {
+            hash_channel(&mut hasher, channel);
+        }
+        hasher.finish()
+    })
+}
+
+/// Serialized layout of every message going through the channels
+fn message_schemas() -> Vec<u8> {
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let roots = vec![
+        tracer.trace_simple_type::<ServerMessage>().unwrap().0,
+        tracer.trace_simple_type::<ClientMessage>().unwrap().0,
+        // Inputs and heaviness of the local players, by slot
+        tracer
+            .trace_simple_type::<(PlayerSlot, PlayerInput)>()
+            .unwrap()
+            .0,
+        tracer.trace_simple_type::<(PlayerSlot, bool)>().unwrap().0,
+        tracer.trace_simple_type::<String>().unwrap().0,
+        tracer.trace_simple_type::<ChatLine>().unwrap().0,
+        tracer.trace_simple_type::<NetworkedEntities>().unwrap().0,
+        // States of the modes, sent serialized in the entities
+        tracer.trace_simple_type::<GrappleAnchors>().unwrap().0,
+        tracer.trace_simple_type::<CaptureProgress>().unwrap().0,
+        tracer.trace_simple_type::<FootballScore>().unwrap().0,
+    ];
+    let registry = tracer.registry().unwrap();
+    bincode::serialize(&(roots, registry)).unwrap()
+}
+
+fn hash_channel(hasher: &mut impl Hasher, channel: &ChannelConfig) {
+    hasher.write(&[channel.channel_id]);
+    hasher.write(&(channel.max_memory_usage_bytes as u64).to_le_bytes());
+    match channel.send_type {
+        SendType::Unreliable => hasher.write(&[0]),
+        SendType::ReliableOrdered { resend_time } => {
+            hasher.write(&[1]);
+            hasher.write(&resend_time.as_nanos().to_le_bytes());
+        }
+        SendType::ReliableUnordered { resend_time } => {
+            hasher.write(&[2]);
+            hasher.write(&resend_time.as_nanos().to_le_bytes());
+        }
+    }
+}
+
+/// 64 bits FNV-1a, unlike the std hashers it gives the same result on every build and platform
+/// as long as only byte slices are written to it
+struct Fnv1aHasher(u64);
+
+impl Default for Fnv1aHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_data_round_trips() {
+        let info = ProtocolInfo {
+            version: 7,
+            id: 0x0123_4567_89ab_cdef,
+        };
+        assert_eq!(ProtocolInfo::from_user_data(&info.to_user_data()), info);
+    }
+
+    #[test]
+    fn protocol_id_is_deterministic() {
+        // Tracing the messages twice checks that their layout does not depend on the run either
+        assert_eq!(message_schemas(), message_schemas());
+        assert_eq!(protocol_id(), protocol_id());
+        assert_eq!(ProtocolInfo::current().id, protocol_id());
+    }
+}
@@ -1,32 +1,79 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::{GameState, Processing, WALL_HALF_HEIGHT, WALL_HALF_WIDTH, WALL_TRANSFORM};
+use crate::{map::Map, GameState, Processing};
 
 pub struct GameScenePlugin;
 
 impl Plugin for GameScenePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::InGame), spawn_scene.in_set(Processing));
+        app.add_systems(OnEnter(GameState::InGame), spawn_scene.in_set(Processing))
+            .add_systems(OnExit(GameState::InGame), despawn_scene);
     }
 }
 
 #[derive(Component)]
-pub struct Wall;
-
-pub fn spawn_scene(mut commands: Commands) {
-    commands.spawn((
-        Wall,
-        TransformBundle::from_transform(WALL_TRANSFORM),
-        RigidBody::Fixed,
-        Collider::cuboid(WALL_HALF_WIDTH, WALL_HALF_HEIGHT),
-        Friction {
-            coefficient: 0.,
-            combine_rule: CoefficientCombineRule::Min,
-        },
-        Restitution {
-            coefficient: 0.99,
-            combine_rule: CoefficientCombineRule::Max,
-        },
-    ));
+pub struct Wall {
+    pub half_size: Vec2,
+    pub grappleable: bool,
+}
+
+/// Sensor covering the capture zone of the map
+#[derive(Component)]
+pub struct CaptureZone;
+
+/// Sensor covering the goal defended by the team
+#[derive(Component)]
+pub struct Goal {
+    pub team: u8,
+}
+
+pub fn spawn_scene(mut commands: Commands, map: Res<Map>) {
+    if let Some(zone) = &map.capture_zone {
+        commands.spawn((
+            CaptureZone,
+            TransformBundle::from_transform(Transform::from_translation(zone.position.extend(0.))),
+            Collider::cuboid(zone.half_size.x, zone.half_size.y),
+            Sensor,
+        ));
+    }
+    let goals = map.pitch.iter().flat_map(|pitch| pitch.goals.iter());
+    for (team, goal) in goals.enumerate() {
+        commands.spawn((
+            Goal { team: team as u8 },
+            TransformBundle::from_transform(Transform::from_translation(goal.position.extend(0.))),
+            Collider::cuboid(goal.half_size.x, goal.half_size.y),
+            Sensor,
+        ));
+    }
+    for platform in map.platforms.iter() {
+        commands.spawn((
+            Wall {
+                half_size: platform.half_size,
+                grappleable: platform.grappleable,
+            },
+            TransformBundle::from_transform(Transform::from_translation(
+                platform.position.extend(0.),
+            )),
+            RigidBody::Fixed,
+            Collider::cuboid(platform.half_size.x, platform.half_size.y),
+            Friction {
+                coefficient: 0.,
+                combine_rule: CoefficientCombineRule::Min,
+            },
+            Restitution {
+                coefficient: 0.99,
+                combine_rule: CoefficientCombineRule::Max,
+            },
+        ));
+    }
+}
+
+pub fn despawn_scene(
+    mut commands: Commands,
+    walls: Query<Entity, Or<(With<Wall>, With<CaptureZone>, With<Goal>)>>,
+) {
+    for wall in walls.iter() {
+        commands.entity(wall).despawn_recursive();
+    }
 }
@@ -4,13 +4,13 @@ use std::{
     time::SystemTime,
 };
 
-use bevy::{app::AppExit, diagnostic::LogDiagnosticsPlugin, prelude::*};
+use bevy::{diagnostic::LogDiagnosticsPlugin, prelude::*};
 use bevy_egui::{EguiContexts, EguiPlugin};
 use bevy_rapier2d::prelude::{NoUserData, RapierConfiguration, RapierPhysicsPlugin, TimestepMode};
 use bevy_renet::{
     renet::{
         transport::{NetcodeServerTransport, ServerAuthentication, ServerConfig},
-        RenetServer, ServerEvent,
+        RenetServer,
     },
     transport::NetcodeServerPlugin,
     RenetServerPlugin,
@@ -19,29 +19,82 @@ use renet_visualizer::RenetServerVisualizer;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    ball::BallsPlugin, connection_config, display::DisplayPlugin, scene::GameScenePlugin,
-    ApplicationSide, GameState, Lobby, PlayerData, Processing, Receiving, Sending, FIXED_DT,
-    PHYSICS_DT, PPM, SUBSTEPS,
+    ball::BallsPlugin,
+    client::{channel::ClientChannel, ClientMessage},
+    client_of, connection_config,
+    map::Map,
+    mode::{GameModes, GameModesPlugin, RoundEndEvent},
+    profile::Profile,
+    protocol::NETCODE_PROTOCOL_ID,
+    scene::GameScenePlugin,
+    team::balance_teams,
+    ApplicationSide, EliminationEvent, GameState, Lobby, MatchResults, PlayerData, PlayerStats,
+    Processing, Receiving, Ruleset, Sending, Teams, FIXED_DT, PHYSICS_DT, PPM, RESULTS_DURATION,
+    SUBSTEPS,
 };
 
-use self::{channel::ServerChannel, communication::ServerCommunicationPlugin};
+use self::{
+    admin::AdminPlugin,
+    channel::ServerChannel,
+    chat::ChatPlugin,
+    communication::ServerCommunicationPlugin,
+    discovery::DiscoveryResponderPlugin,
+    handshake::HandshakePlugin,
+    room::{check_ruleset, PlayerJoinedEvent, PlayerLeftEvent, RoomInfo, RoomServer, RoomsPlugin},
+};
 
+pub mod admin;
 pub mod channel;
+pub mod chat;
 pub mod communication;
+pub mod discovery;
+pub mod handshake;
+pub mod room;
 
 pub struct ServerPlugin {
     pub public_addr: SocketAddr,
-    pub protocol_id: u64,
+    /// Name shown to the clients browsing the local network
+    pub name: String,
+    /// Whether to answer the discovery queries of the clients
+    pub discovery: bool,
 }
 
 impl Plugin for ServerPlugin {
     fn build(&self, app: &mut App) {
         let (server, transport) = self.new_renet_server();
+        app.insert_resource(server)
+            .insert_resource(transport)
+            .add_plugins(DefaultPlugins)
+            .add_plugins((RenetServerPlugin, NetcodeServerPlugin))
+            .add_plugins((
+                // FrameTimeDiagnosticsPlugin,
+                LogDiagnosticsPlugin::default(),
+                EguiPlugin,
+            ))
+            .insert_resource(RenetServerVisualizer::<200>::default())
+            .add_plugins((HandshakePlugin, RoomsPlugin, ChatPlugin, AdminPlugin))
+            .add_systems(Update, update_visualizer_system);
+        if self.discovery {
+            app.add_plugins(DiscoveryResponderPlugin {
+                name: self.name.clone(),
+                port: self.public_addr.port(),
+            });
+        }
+    }
+}
+
+/// Game logic of a single room, built into the own world of the room
+pub(crate) struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
         app.add_state::<GameState>()
             .insert_resource(Lobby::default())
+            .insert_resource(MatchResults::default())
             .insert_resource(ApplicationSide::Server)
-            .insert_resource(server)
-            .insert_resource(transport)
+            .insert_resource(RoomServer::default())
+            .insert_resource(RoomHost::default())
+            .insert_resource(StartingStats::default())
             .insert_resource(RapierConfiguration {
                 timestep_mode: TimestepMode::Fixed {
                     dt: PHYSICS_DT,
@@ -49,46 +102,113 @@ impl Plugin for ServerPlugin {
                 },
                 ..default()
             })
-            .add_plugins(DefaultPlugins)
             .insert_resource(FixedTime::new_from_secs(FIXED_DT))
-            .add_plugins((
-                RenetServerPlugin,
-                NetcodeServerPlugin,
-                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PPM),
-            ))
-            .add_plugins((
-                // FrameTimeDiagnosticsPlugin,
-                LogDiagnosticsPlugin::default(),
-                EguiPlugin,
-            ))
-            .insert_resource(RenetServerVisualizer::<200>::default())
+            .add_event::<PlayerJoinedEvent>()
+            .add_event::<PlayerLeftEvent>()
+            .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PPM))
             .add_plugins((BallsPlugin, GameScenePlugin))
-            .add_plugins(DisplayPlugin)
-            .add_plugins(ServerCommunicationPlugin)
+            .add_plugins((ServerCommunicationPlugin, GameModesPlugin))
             .configure_sets(FixedUpdate, (Receiving, Processing, Sending).chain())
             .configure_sets(
                 OnEnter(GameState::InGame),
                 (Receiving, Processing, Sending).chain(),
             )
-            .add_systems(OnEnter(GameState::InGame), start_game.in_set(Sending))
+            .add_systems(
+                OnEnter(GameState::InGame),
+                (assign_teams.in_set(Receiving), start_game.in_set(Sending)),
+            )
             .add_systems(OnEnter(GameState::Lobby), start_lobby.in_set(Sending))
-            .add_systems(OnExit(GameState::InGame), stop.in_set(Sending))
-            .add_systems(Update, update_visualizer_system)
+            .add_systems(OnEnter(GameState::Results), start_results)
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_room_members,
+                        receive_client_messages,
+                        broadcast_lobby_changes,
+                    )
+                        .chain(),
+                    tick_results_timer.run_if(in_state(GameState::Results)),
+                ),
+            )
             .add_systems(
                 FixedUpdate,
-                (receive_server_events.in_set(Receiving), check_player_count),
+                (
+                    broadcast_eliminations.in_set(Sending),
+                    (check_round_end, end_round)
+                        .chain()
+                        .after(Processing)
+                        .before(Sending)
+                        .run_if(in_state(GameState::InGame)),
+                ),
             );
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, Component)]
 pub enum ServerMessage {
+    /// The server disconnects the client right after, for instance because it does not speak
+    /// the protocol of the server
+    ///
+    /// Must stay the first variant with this layout so that clients of every version can read it
+    Rejected {
+        reason: String,
+    },
+    RoomList {
+        rooms: Vec<RoomInfo>,
+    },
+    JoinedRoom {
+        room: RoomInfo,
+    },
+    LeftRoom,
+    /// Players of the room along with the settings of the room, sent whenever they change
+    LobbyUpdate {
+        players: HashMap<u64, PlayerData>,
+        host: Option<u64>,
+        map: String,
+        ruleset: Ruleset,
+    },
+    /// A room request of the client could not be fulfilled
+    RoomError {
+        reason: String,
+    },
     EnterLobby,
-    EnterGame { players: HashMap<u64, PlayerData> },
-    Stop,
-    PlayerLeavedInGame { player_id: u64 },
+    EnterGame {
+        players: HashMap<u64, PlayerData>,
+        map: Map,
+    },
+    EnterResults {
+        results: MatchResults,
+    },
+    /// The server is shutting down for the given reason
+    Stop {
+        reason: String,
+    },
+    PlayerLeavedInGame {
+        player_id: u64,
+    },
+    PlayerEliminated {
+        victim: u64,
+        killer: Option<u64>,
+    },
+    /// Round trip times of the members of the room, in milliseconds
+    Pings {
+        pings: HashMap<u64, u32>,
+    },
 }
 
+/// Player choosing the settings of the room and starting the matches
+#[derive(Debug, Default, Resource)]
+struct RoomHost(Option<u64>);
+
+/// Counts down the time spent on the results screen
+#[derive(Resource, Deref, DerefMut)]
+struct ResultsTimer(Timer);
+
+/// Statistics of the players when the match started, to tell the ones gained during the match
+#[derive(Debug, Default, Resource)]
+struct StartingStats(HashMap<u64, PlayerStats>);
+
 impl ServerPlugin {
     fn new_renet_server(&self) -> (RenetServer, NetcodeServerTransport) {
         let server = RenetServer::new(connection_config());
@@ -96,7 +216,7 @@ impl ServerPlugin {
         let socket = UdpSocket::bind(self.public_addr).unwrap();
         let server_config = ServerConfig {
             max_clients: 64,
-            protocol_id: self.protocol_id,
+            protocol_id: NETCODE_PROTOCOL_ID,
             public_addr: self.public_addr,
             authentication: ServerAuthentication::Unsecure,
         };
@@ -110,90 +230,420 @@ impl ServerPlugin {
     }
 }
 
-fn receive_server_events(
+fn handle_room_members(
     mut commands: Commands,
-    mut server_events: EventReader<ServerEvent>,
-    mut server: ResMut<RenetServer>,
-    state: ResMut<State<GameState>>,
+    mut joined_events: EventReader<PlayerJoinedEvent>,
+    mut left_events: EventReader<PlayerLeftEvent>,
+    mut server: ResMut<RoomServer>,
+    state: Res<State<GameState>>,
     mut players: ResMut<Lobby>,
-    mut visualizer: ResMut<RenetServerVisualizer<200>>,
+    mut host: ResMut<RoomHost>,
+    map: Res<Map>,
 ) {
-    for event in server_events.iter() {
-        match event {
-            ServerEvent::ClientConnected { client_id } => {
-                println!("Player joined with client id {}", client_id);
-                players.players.insert(*client_id, PlayerData::default());
-                visualizer.add_client(*client_id);
+    for &PlayerJoinedEvent {
+        player_id,
+        spectator,
+    } in joined_events.iter()
+    {
+        println!("Player {} joined the room", player_id);
+        let profile =
+            Profile::default().sanitized(players.players.values().map(|data| &data.profile));
+        players.players.insert(
+            player_id,
+            PlayerData {
+                profile,
+                spectator,
+                ..default()
+            },
+        );
+        if host.0.is_none() {
+            host.0 = Some(player_id);
+        }
+        // Players joining mid-match watch it until the next one
+        if *state.get() == GameState::InGame {
+            let message = bincode::serialize(&ServerMessage::EnterGame {
+                players: players.players.clone(),
+                map: map.clone(),
+            })
+            .unwrap();
+            server.send_message(player_id, ServerChannel::ServerMessages, message);
+        }
+    }
+    for PlayerLeftEvent { player_id } in left_events.iter() {
+        println!("Player {} left the room", player_id);
+        let Some(data) = players.players.remove(player_id) else {
+            continue;
+        };
+        if host.0 == Some(*player_id) {
+            host.0 = players.players.keys().next().copied();
+        }
+        if *state.get() == GameState::InGame {
+            server.broadcast_message(
+                ServerChannel::ServerMessages,
+                bincode::serialize(&ServerMessage::PlayerLeavedInGame {
+                    player_id: *player_id,
+                })
+                .unwrap(),
+            );
+            // The ball may already have been eliminated
+            if let Some(entity) = data.entity {
+                commands.entity(entity).despawn_recursive();
             }
-            ServerEvent::ClientDisconnected { client_id, reason } => {
-                println!(
-                    "Player with client id {} left with reason \"{}\"",
-                    client_id, reason
-                );
-                visualizer.remove_client(*client_id);
-                match state.get() {
-                    GameState::InGame => {
-                        server.broadcast_message(
-                            ServerChannel::ServerMessages,
-                            bincode::serialize(&ServerMessage::PlayerLeavedInGame {
-                                player_id: *client_id,
+        }
+    }
+}
+
+/// Handles the requests of the members of the room forwarded by the main world
+fn receive_client_messages(
+    mut server: ResMut<RoomServer>,
+    mut lobby: ResMut<Lobby>,
+    host: Res<RoomHost>,
+    mut map: ResMut<Map>,
+    mut ruleset: ResMut<Ruleset>,
+    mut results: ResMut<MatchResults>,
+    modes: Res<GameModes>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let ids: Vec<u64> = lobby.players.keys().copied().collect();
+    // Each local player of a client sends its own requests, the ones about the room coming from
+    // the first one
+    for sender in ids {
+        while let Some(message) = server.receive_message(sender, ClientChannel::ClientMessages) {
+            let Ok(message) = bincode::deserialize::<ClientMessage>(&message) else {
+                continue;
+            };
+            // Every local player of the client of the host is trusted as much as the host
+            let is_host = host.0.map(client_of) == Some(client_of(sender));
+            let result = match message {
+                ClientMessage::SetProfile { profile, .. } => {
+                    let others = lobby
+                        .players
+                        .iter()
+                        .filter(|(&id, _)| id != sender)
+                        .map(|(_, data)| &data.profile);
+                    let profile = profile.sanitized(others);
+                    if let Some(data) = lobby.players.get_mut(&sender) {
+                        data.profile = profile;
+                    }
+                    Ok(())
+                }
+                ClientMessage::SetReady { ready, .. } => {
+                    if let Some(data) = lobby.players.get_mut(&sender) {
+                        data.ready = ready;
+                    }
+                    Ok(())
+                }
+                ClientMessage::SetSpectator { spectator, .. } => {
+                    let is_spectator = lobby
+                        .players
+                        .get(&sender)
+                        .map_or(false, |data| data.spectator);
+                    if *state.get() != GameState::Lobby {
+                        Err("Spectating can only be toggled in the lobby".to_owned())
+                    } else if is_spectator
+                        && !spectator
+                        && lobby.playing().count() >= ruleset.max_players
+                    {
+                        Err("There is no room left for another player".to_owned())
+                    } else {
+                        if let Some(data) = lobby.players.get_mut(&sender) {
+                            data.spectator = spectator;
+                            data.ready = false;
+                            data.team = None;
+                        }
+                        Ok(())
+                    }
+                }
+                ClientMessage::ChooseTeam { team, .. } => {
+                    if *state.get() != GameState::Lobby {
+                        Err("Teams can only be chosen in the lobby".to_owned())
+                    } else if lobby
+                        .players
+                        .get(&sender)
+                        .map_or(false, |data| data.spectator)
+                    {
+                        Err("Spectators can't join a team".to_owned())
+                    } else if team.map_or(false, |team| team >= ruleset.teams.count()) {
+                        Err("This team doesn't exist".to_owned())
+                    } else {
+                        if let Some(data) = lobby.players.get_mut(&sender) {
+                            data.team = team;
+                        }
+                        Ok(())
+                    }
+                }
+                ClientMessage::ChangeRoomSettings {
+                    map: map_name,
+                    ruleset: new_ruleset,
+                } => {
+                    if !is_host {
+                        Err("Only the host can change the settings of the room".to_owned())
+                    } else if *state.get() != GameState::Lobby {
+                        Err("The settings can only be changed in the lobby".to_owned())
+                    } else if new_ruleset.max_players < lobby.playing().count() {
+                        Err("There are already more players in the room".to_owned())
+                    } else {
+                        Map::find(&map_name)
+                            .ok_or_else(|| format!("Unknown map \"{}\"", map_name))
+                            .and_then(|new_map| {
+                                check_ruleset(&new_ruleset, &new_map, &modes)?;
+                                let teams_changed = new_ruleset.teams != ruleset.teams;
+                                *map = new_map;
+                                *ruleset = new_ruleset;
+                                // Players have to agree to the new settings
+                                for data in lobby.players.values_mut() {
+                                    data.ready = false;
+                                    if teams_changed {
+                                        data.team = None;
+                                    }
+                                }
+                                if teams_changed {
+                                    results.team_scores.clear();
+                                }
+                                Ok(())
                             })
-                            .unwrap(),
+                    }
+                }
+                ClientMessage::KickPlayer { player_id } => {
+                    if !is_host {
+                        Err("Only the host can kick players".to_owned())
+                    } else if client_of(player_id) == client_of(sender)
+                        || !lobby.players.contains_key(&player_id)
+                    {
+                        Err("This player can't be kicked".to_owned())
+                    } else {
+                        server.kick(
+                            player_id,
+                            "You were kicked from the room by the host".to_owned(),
                         );
-                        // TODO deal with these unwraps
-                        commands
-                            .get_entity(players.players.get(client_id).unwrap().entity.unwrap())
-                            .unwrap()
-                            .despawn_recursive();
+                        Ok(())
                     }
-                    GameState::Lobby => (),
                 }
-                players.players.remove(client_id);
+                ClientMessage::StartMatch => {
+                    if !is_host {
+                        Err("Only the host can start the match".to_owned())
+                    } else if *state.get() != GameState::Lobby {
+                        Err("The match has already started".to_owned())
+                    } else if lobby.playing().count() < ruleset.min_players {
+                        Err(format!(
+                            "At least {} players are needed to start",
+                            ruleset.min_players
+                        ))
+                    } else if lobby
+                        .playing()
+                        .any(|(&id, data)| client_of(id) != client_of(sender) && !data.ready)
+                    {
+                        Err("Not everyone is ready".to_owned())
+                    } else {
+                        next_state.set(GameState::InGame);
+                        Ok(())
+                    }
+                }
+                // Handled by the main world
+                _ => Ok(()),
+            };
+            if let Err(reason) = result {
+                let message = bincode::serialize(&ServerMessage::RoomError { reason }).unwrap();
+                server.send_message(sender, ServerChannel::ServerMessages, message);
             }
         }
     }
 }
 
-fn start_lobby(mut server: ResMut<RenetServer>) {
+/// Tells the members about the players, host and settings of the room whenever they change
+fn broadcast_lobby_changes(
+    mut server: ResMut<RoomServer>,
+    lobby: Res<Lobby>,
+    host: Res<RoomHost>,
+    map: Res<Map>,
+    ruleset: Res<Ruleset>,
+) {
+    if !(lobby.is_changed() || host.is_changed() || map.is_changed() || ruleset.is_changed()) {
+        return;
+    }
+    let message = bincode::serialize(&ServerMessage::LobbyUpdate {
+        players: lobby.players.clone(),
+        host: host.0,
+        map: map.name.clone(),
+        ruleset: ruleset.clone(),
+    })
+    .unwrap();
+    server.broadcast_message(ServerChannel::ServerMessages, message);
+}
+
+fn start_lobby(mut server: ResMut<RoomServer>, mut lobby: ResMut<Lobby>) {
+    for data in lobby.players.values_mut() {
+        data.ready = false;
+    }
     // TODO check if this unwrap is safe
     let message = bincode::serialize(&ServerMessage::EnterLobby).unwrap();
     server.broadcast_message(ServerChannel::ServerMessages, message);
     println!("Starting lobby");
 }
 
-fn start_game(mut server: ResMut<RenetServer>, lobby: Res<Lobby>) {
+fn start_game(
+    mut server: ResMut<RoomServer>,
+    lobby: Res<Lobby>,
+    map: Res<Map>,
+    mut starting_stats: ResMut<StartingStats>,
+) {
+    starting_stats.0 = lobby
+        .players
+        .iter()
+        .map(|(&id, data)| (id, data.stats))
+        .collect();
     let message = bincode::serialize(&ServerMessage::EnterGame {
         players: lobby.players.clone(),
+        map: map.clone(),
     })
     .unwrap();
     server.broadcast_message(ServerChannel::ServerMessages, message);
     println!("Starting game...");
 }
 
-fn stop(mut exit: EventWriter<AppExit>, mut server: ResMut<RenetServer>) {
-    let message = bincode::serialize(&ServerMessage::Stop).unwrap();
+/// Counts the wins and ranks the players before showing the results
+fn start_results(
+    mut commands: Commands,
+    mut server: ResMut<RoomServer>,
+    mut results: ResMut<MatchResults>,
+    mut lobby: ResMut<Lobby>,
+    starting_stats: Res<StartingStats>,
+) {
+    let mut rankings = Vec::new();
+    for (&id, data) in lobby.players.iter_mut().filter(|(_, data)| !data.spectator) {
+        let won = match results.winning_team {
+            Some(team) => data.team == Some(team),
+            None => results.winner == Some(id),
+        };
+        if won {
+            data.stats.wins += 1;
+        }
+        let starting = starting_stats.0.get(&id).copied().unwrap_or_default();
+        rankings.push((id, data.stats.since(starting)));
+    }
+    rank_players(&mut rankings);
+    results.rankings = rankings;
+    let message = bincode::serialize(&ServerMessage::EnterResults {
+        results: results.clone(),
+    })
+    .unwrap();
     server.broadcast_message(ServerChannel::ServerMessages, message);
-    println!("Exiting...");
-    exit.send(AppExit);
+    commands.insert_resource(ResultsTimer(Timer::new(RESULTS_DURATION, TimerMode::Once)));
+    println!("Match over, winner: {:?}", results.winner);
 }
 
-fn check_player_count(
-    lobby: Res<Lobby>,
-    state: Res<State<GameState>>,
+/// Winners first, then the players who knocked out the most balls and fell the least
+fn rank_players(rankings: &mut [(u64, PlayerStats)]) {
+    rankings.sort_by_key(|(_, stats)| {
+        (
+            std::cmp::Reverse(stats.wins),
+            std::cmp::Reverse(stats.kills),
+            stats.deaths,
+        )
+    });
+}
+
+fn tick_results_timer(
+    mut timer: ResMut<ResultsTimer>,
+    time: Res<Time>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    match state.get() {
-        GameState::Lobby => {
-            if lobby.players.len() >= 2 {
-                next_state.set(GameState::InGame);
+    if timer.tick(time.delta()).just_finished() {
+        next_state.set(GameState::Lobby);
+    }
+}
+
+/// Ends the match once at most one ball, or the balls of a single team, are left, which also
+/// covers players leaving mid-game
+fn check_round_end(
+    lobby: Res<Lobby>,
+    ruleset: Res<Ruleset>,
+    modes: Res<GameModes>,
+    mut round_end_writer: EventWriter<RoundEndEvent>,
+) {
+    // Rounds without any ball left end whatever the mode
+    let empty = lobby.players.values().all(|data| data.entity.is_none());
+    if !modes.get(ruleset.mode).last_standing_wins() && !empty {
+        return;
+    }
+    let alive: Vec<_> = lobby
+        .players
+        .iter()
+        .filter(|(_, data)| data.entity.is_some())
+        .collect();
+    let winner = match alive.as_slice() {
+        [] => None,
+        [(id, _)] => Some(**id),
+        _ if ruleset.teams == Teams::FreeForAll => return,
+        [(_, first), others @ ..] => {
+            if others.iter().any(|(_, data)| data.team != first.team) {
+                return;
             }
+            None
         }
-        GameState::InGame => {
-            if lobby.players.len() < 2 {
-                next_state.set(GameState::Lobby);
-            }
+    };
+    round_end_writer.send(RoundEndEvent {
+        winner,
+        winning_team: alive.first().and_then(|(_, data)| data.team),
+    });
+}
+
+/// Records the winner of the round ended by the rules of the mode and shows the results, the
+/// winning team only counting in the team modes
+fn end_round(
+    mut round_end_reader: EventReader<RoundEndEvent>,
+    ruleset: Res<Ruleset>,
+    mut results: ResMut<MatchResults>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Only the first rule ending the round in a tick counts
+    let Some((winner, winning_team)) = round_end_reader
+        .iter()
+        .map(|event| (event.winner, event.winning_team))
+        .next()
+    else {
+        return;
+    };
+    round_end_reader.clear();
+    results.winner = winner;
+    results.winning_team = None;
+    if ruleset.teams != Teams::FreeForAll {
+        results.winning_team = winning_team;
+        results
+            .team_scores
+            .resize(ruleset.teams.count() as usize, 0);
+        if let Some(team) = winning_team {
+            results.team_scores[team as usize] += 1;
         }
     }
+    next_state.set(GameState::Results);
+}
+
+/// Gives a team to the players who did not choose one
+fn assign_teams(mut lobby: ResMut<Lobby>, ruleset: Res<Ruleset>) {
+    balance_teams(
+        ruleset.teams,
+        lobby
+            .players
+            .values_mut()
+            .filter(|data| !data.spectator)
+            .map(|data| &mut data.team),
+    );
+}
+
+/// Counts the kills and deaths of the players and tells the members about the eliminations
+fn broadcast_eliminations(
+    mut server: ResMut<RoomServer>,
+    mut lobby: ResMut<Lobby>,
+    mut event_reader: EventReader<EliminationEvent>,
+) {
+    for &EliminationEvent { victim, killer } in event_reader.iter() {
+        lobby.record_elimination(victim, killer);
+        let message =
+            bincode::serialize(&ServerMessage::PlayerEliminated { victim, killer }).unwrap();
+        server.broadcast_message(ServerChannel::ServerMessages, message);
+    }
 }
 
 fn update_visualizer_system(
@@ -204,3 +654,29 @@ fn update_visualizer_system(
     visualizer.update(&server);
     visualizer.show_window(egui_contexts.ctx_mut());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(wins: u32, kills: u32, deaths: u32) -> PlayerStats {
+        PlayerStats {
+            wins,
+            kills,
+            deaths,
+        }
+    }
+
+    #[test]
+    fn players_are_ranked_by_wins_kills_then_deaths() {
+        let mut rankings = vec![
+            (1, stats(0, 3, 1)),
+            (2, stats(0, 3, 0)),
+            (3, stats(1, 0, 2)),
+            (4, stats(0, 5, 4)),
+        ];
+        rank_players(&mut rankings);
+        let order: Vec<u64> = rankings.iter().map(|(id, _)| *id).collect();
+        assert_eq!(order, [3, 4, 2, 1]);
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    io::stdin,
+    str::FromStr,
+    sync::{
+        mpsc::{channel, Receiver},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_renet::renet::RenetServer;
+
+use super::{
+    channel::ServerChannel, chat::ChatModeration, handshake::RejectedClients, ServerMessage,
+};
+
+/// Time left to the clients to receive the shutdown notification before the server exits
+const SHUTDOWN_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_SHUTDOWN_REASON: &str = "Server closed by the administrator";
+const DEFAULT_KICK_REASON: &str = "Kicked by the administrator";
+
+/// Reads administration commands from the standard input of the server process
+pub struct AdminPlugin;
+
+impl Plugin for AdminPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for line in stdin().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                match line.parse::<AdminCommand>() {
+                    Ok(command) => {
+                        if sender.send(command).is_err() {
+                            break;
+                        }
+                    }
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
+        });
+        app.insert_resource(AdminCommands(Mutex::new(receiver)))
+            .add_systems(
+                Update,
+                (
+                    handle_admin_commands,
+                    exit_after_shutdown.run_if(resource_exists::<ShutdownTimer>()),
+                ),
+            );
+    }
+}
+
+#[derive(Debug)]
+pub enum AdminCommand {
+    /// Notifies every client with the given reason and stops the server
+    Shutdown {
+        reason: String,
+    },
+    /// Disconnects a client from the server
+    Kick {
+        client_id: u64,
+        reason: String,
+    },
+    /// Prevents a client from chatting
+    Mute {
+        client_id: u64,
+    },
+    Unmute {
+        client_id: u64,
+    },
+}
+
+impl FromStr for AdminCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+        match command {
+            "stop" | "shutdown" => Ok(AdminCommand::Shutdown {
+                reason: if argument.is_empty() {
+                    DEFAULT_SHUTDOWN_REASON.to_owned()
+                } else {
+                    argument.to_owned()
+                },
+            }),
+            "kick" => {
+                let (client_id, reason) = argument.split_once(' ').unwrap_or((argument, ""));
+                let client_id = client_id
+                    .parse()
+                    .map_err(|_| "Usage: kick <client id> [reason]".to_owned())?;
+                let reason = reason.trim();
+                Ok(AdminCommand::Kick {
+                    client_id,
+                    reason: if reason.is_empty() {
+                        DEFAULT_KICK_REASON.to_owned()
+                    } else {
+                        reason.to_owned()
+                    },
+                })
+            }
+            "mute" | "unmute" => {
+                let client_id = argument
+                    .parse()
+                    .map_err(|_| format!("Usage: {} <client id>", command))?;
+                Ok(if command == "mute" {
+                    AdminCommand::Mute { client_id }
+                } else {
+                    AdminCommand::Unmute { client_id }
+                })
+            }
+            _ => Err(format!(
+                "Unknown command \"{}\", available commands: stop [reason], kick <client id> [reason], mute <client id>, unmute <client id>",
+                command
+            )),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct AdminCommands(Mutex<Receiver<AdminCommand>>);
+
+#[derive(Resource, Deref, DerefMut)]
+struct ShutdownTimer(Timer);
+
+fn handle_admin_commands(
+    mut commands: Commands,
+    admin_commands: Res<AdminCommands>,
+    mut server: ResMut<RenetServer>,
+    shutdown: Option<Res<ShutdownTimer>>,
+    mut rejected: ResMut<RejectedClients>,
+    mut moderation: ResMut<ChatModeration>,
+) {
+    let mut shutting_down = shutdown.is_some();
+    let receiver = admin_commands.0.lock().unwrap();
+    while let Ok(command) = receiver.try_recv() {
+        match command {
+            AdminCommand::Shutdown { reason } => {
+                if shutting_down {
+                    continue;
+                }
+                shutting_down = true;
+                println!("Shutting down: {}", reason);
+                let message = bincode::serialize(&ServerMessage::Stop { reason }).unwrap();
+                server.broadcast_message(ServerChannel::ServerMessages, message);
+                commands
+                    .insert_resource(ShutdownTimer(Timer::new(SHUTDOWN_DELAY, TimerMode::Once)));
+            }
+            AdminCommand::Kick { client_id, reason } => {
+                if server.is_connected(client_id) {
+                    rejected.reject(&mut server, client_id, reason);
+                } else {
+                    eprintln!("No client with id {}", client_id);
+                }
+            }
+            AdminCommand::Mute { client_id } => {
+                if moderation.mute(client_id) {
+                    println!("Muted client {}", client_id);
+                }
+            }
+            AdminCommand::Unmute { client_id } => {
+                if moderation.unmute(client_id) {
+                    println!("Unmuted client {}", client_id);
+                }
+            }
+        }
+    }
+}
+
+fn exit_after_shutdown(
+    mut timer: ResMut<ShutdownTimer>,
+    time: Res<Time>,
+    mut server: ResMut<RenetServer>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if timer.tick(time.delta()).just_finished() {
+        server.disconnect_all();
+        println!("Exiting...");
+        exit.send(AppExit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shutdown_takes_an_optional_reason() {
+        assert!(matches!(
+            "stop".parse::<AdminCommand>(),
+            Ok(AdminCommand::Shutdown { reason }) if reason == DEFAULT_SHUTDOWN_REASON
+        ));
+        assert!(matches!(
+            "shutdown  maintenance soon ".parse::<AdminCommand>(),
+            Ok(AdminCommand::Shutdown { reason }) if reason == "maintenance soon"
+        ));
+    }
+
+    #[test]
+    fn kick_takes_a_client_id_and_an_optional_reason() {
+        assert!(matches!(
+            "kick 42".parse::<AdminCommand>(),
+            Ok(AdminCommand::Kick { client_id: 42, reason }) if reason == DEFAULT_KICK_REASON
+        ));
+        assert!(matches!(
+            "kick 42 be nice".parse::<AdminCommand>(),
+            Ok(AdminCommand::Kick { client_id: 42, reason }) if reason == "be nice"
+        ));
+        assert!("kick".parse::<AdminCommand>().is_err());
+        assert!("kick bob".parse::<AdminCommand>().is_err());
+    }
+
+    #[test]
+    fn mute_and_unmute_take_a_client_id() {
+        assert!(matches!(
+            "mute 7".parse::<AdminCommand>(),
+            Ok(AdminCommand::Mute { client_id: 7 })
+        ));
+        assert!(matches!(
+            "unmute 7".parse::<AdminCommand>(),
+            Ok(AdminCommand::Unmute { client_id: 7 })
+        ));
+        assert!("mute".parse::<AdminCommand>().is_err());
+        assert!("unmute -1".parse::<AdminCommand>().is_err());
+    }
+
+    #[test]
+    fn unknown_commands_are_refused() {
+        assert!("ban 7".parse::<AdminCommand>().is_err());
+        assert!("".parse::<AdminCommand>().is_err());
+    }
+}
@@ -7,6 +7,7 @@ pub enum ServerChannel {
     PlayerInput,
     PlayerHeaviness,
     NetworkedEntities,
+    Chat,
 }
 
 impl From<ServerChannel> for u8 {
@@ -16,6 +17,7 @@ impl From<ServerChannel> for u8 {
             ServerChannel::PlayerInput => 1,
             ServerChannel::PlayerHeaviness => 2,
             ServerChannel::NetworkedEntities => 3,
+            ServerChannel::Chat => 4,
         }
     }
 }
@@ -45,6 +47,13 @@ impl ServerChannel {
                 max_memory_usage_bytes: 10 * 1024 * 1024,
                 send_type: SendType::Unreliable,
             },
+            ChannelConfig {
+                channel_id: Self::Chat.into(),
+                max_memory_usage_bytes: 1024 * 1024,
+                send_type: SendType::ReliableOrdered {
+                    resend_time: Duration::from_millis(200),
+                },
+            },
         ]
     }
 }
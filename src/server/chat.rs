@@ -0,0 +1,195 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_renet::renet::{RenetServer, ServerEvent};
+use serde::{Deserialize, Serialize};
+
+use crate::client::channel::ClientChannel;
+
+use super::{channel::ServerChannel, handshake::RejectedClients, room::Rooms};
+
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 200;
+/// Messages a client may send within `FLOOD_WINDOW` before the next ones are dropped
+const FLOOD_MESSAGES: usize = 5;
+const FLOOD_WINDOW: Duration = Duration::from_secs(5);
+/// Words masked out of the messages, matched case-insensitively inside of each word
+const FILTERED_WORDS: &[&str] = &["fuck", "shit", "bitch", "cunt", "asshole", "bastard"];
+
+/// Relays the chat messages of the clients to the other members of their room
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChatModeration::default())
+            .add_systems(Update, (forget_disconnected_clients, relay_chat_messages));
+    }
+}
+
+/// Line of the chat sent on the `ServerChannel::Chat` channel
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatLine {
+    /// Client who wrote the message, none for the notices of the server
+    pub sender: Option<u64>,
+    pub text: String,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct ChatModeration {
+    muted: HashSet<u64>,
+    /// Times of the last messages of each client
+    history: HashMap<u64, VecDeque<Instant>>,
+}
+
+impl ChatModeration {
+    /// Prevents the client from chatting, returns whether it was not muted already
+    pub fn mute(&mut self, client_id: u64) -> bool {
+        self.muted.insert(client_id)
+    }
+
+    /// Returns whether the client was muted
+    pub fn unmute(&mut self, client_id: u64) -> bool {
+        self.muted.remove(&client_id)
+    }
+
+    /// Checks whether the client may send a message now, and records it if so
+    fn check(&mut self, client_id: u64) -> Result<(), &'static str> {
+        if self.muted.contains(&client_id) {
+            return Err("You are muted");
+        }
+        let now = Instant::now();
+        let history = self.history.entry(client_id).or_default();
+        while history
+            .front()
+            .map_or(false, |&time| now.duration_since(time) > FLOOD_WINDOW)
+        {
+            history.pop_front();
+        }
+        if history.len() >= FLOOD_MESSAGES {
+            return Err("You are sending messages too fast");
+        }
+        history.push_back(now);
+        Ok(())
+    }
+}
+
+/// Trims the message to the allowed length and masks the filtered words
+fn sanitize(text: &str) -> String {
+    let text: String = text
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_CHAT_MESSAGE_LENGTH)
+        .collect();
+    text.split(' ')
+        .map(|word| {
+            let lowercase = word.to_lowercase();
+            if FILTERED_WORDS
+                .iter()
+                .any(|filtered| lowercase.contains(filtered))
+            {
+                "*".repeat(word.chars().count())
+            } else {
+                word.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn send_chat_line(server: &mut RenetServer, client_id: u64, line: &ChatLine) {
+    let message = bincode::serialize(line).unwrap();
+    server.send_message(client_id, ServerChannel::Chat, message);
+}
+
+fn relay_chat_messages(
+    mut server: ResMut<RenetServer>,
+    rooms: Res<Rooms>,
+    mut moderation: ResMut<ChatModeration>,
+    rejected: Res<RejectedClients>,
+) {
+    for client_id in server.clients_id() {
+        if rejected.contains(client_id) {
+            continue;
+        }
+        while let Some(message) = server.receive_message(client_id, ClientChannel::Chat) {
+            let Ok(text) = bincode::deserialize::<String>(&message) else {
+                continue;
+            };
+            let text = sanitize(&text);
+            if text.is_empty() {
+                continue;
+            }
+            // Chatting is only possible with the other members of a room
+            let Some(clients) = rooms.clients_in_room_of(client_id) else {
+                continue;
+            };
+            if let Err(notice) = moderation.check(client_id) {
+                let notice = ChatLine {
+                    sender: None,
+                    text: notice.to_owned(),
+                };
+                send_chat_line(&mut server, client_id, &notice);
+                continue;
+            }
+            let line = ChatLine {
+                sender: Some(client_id),
+                text,
+            };
+            for client_id in clients {
+                send_chat_line(&mut server, client_id, &line);
+            }
+        }
+    }
+}
+
+fn forget_disconnected_clients(
+    mut server_events: EventReader<ServerEvent>,
+    mut moderation: ResMut<ChatModeration>,
+) {
+    for event in server_events.iter() {
+        if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+            moderation.history.remove(client_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_capped_and_cleaned_up() {
+        let long = "a".repeat(MAX_CHAT_MESSAGE_LENGTH + 10);
+        assert_eq!(sanitize(&long).len(), MAX_CHAT_MESSAGE_LENGTH);
+        assert_eq!(sanitize("  hel\u{7}lo\n "), "hello");
+    }
+
+    #[test]
+    fn filtered_words_are_masked() {
+        assert_eq!(sanitize("well Shitty game"), "well ****** game");
+        assert_eq!(sanitize("good game"), "good game");
+    }
+
+    #[test]
+    fn flooding_clients_are_held_back() {
+        let mut moderation = ChatModeration::default();
+        for _ in 0..FLOOD_MESSAGES {
+            assert!(moderation.check(1).is_ok());
+        }
+        assert!(moderation.check(1).is_err());
+        // Other clients are not held back along
+        assert!(moderation.check(2).is_ok());
+    }
+
+    #[test]
+    fn muted_clients_cannot_chat() {
+        let mut moderation = ChatModeration::default();
+        assert!(moderation.mute(1));
+        assert_eq!(moderation.check(1), Err("You are muted"));
+        assert!(moderation.unmute(1));
+        assert!(moderation.check(1).is_ok());
+    }
+}
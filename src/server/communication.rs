@@ -1,14 +1,14 @@
-use std::collections::HashMap;
-
 use bevy::prelude::*;
-use bevy_renet::renet::RenetServer;
+use bevy_rapier2d::prelude::Velocity;
 
 use crate::{
-    ball::Ball, client::channel::ClientChannel, server::channel::ServerChannel, DirectionVector,
-    GameState, HeavinessReceivedEvent, Heavy, InputReceivedEvent, Lobby, PlayerInput,
+    ball::Ball, client::channel::ClientChannel, mode::ModeStateSnapshot,
+    server::channel::ServerChannel, BallCollision, DirectionVector, GameState,
+    HeavinessReceivedEvent, Heavy, InputReceivedEvent, Lobby, NetworkedEntities, PlayerInput,
+    ReplicatedBody,
 };
 
-use super::{Receiving, Sending};
+use super::{room::RoomServer, Receiving, Sending};
 
 pub struct ServerCommunicationPlugin;
 
@@ -30,35 +30,37 @@ impl Plugin for ServerCommunicationPlugin {
 }
 
 pub fn receive_player_inputs(
-    mut server: ResMut<RenetServer>,
+    mut server: ResMut<RoomServer>,
     lobby: Res<Lobby>,
     mut event_writer: EventWriter<InputReceivedEvent>,
 ) {
     for &origin in lobby.players.keys() {
         while let Some(message) = server.receive_message(origin, ClientChannel::PlayerInput) {
-            // TODO unwrap
-            let input: PlayerInput = bincode::deserialize(&message).unwrap();
+            let Ok(input) = bincode::deserialize::<PlayerInput>(&message) else {
+                continue;
+            };
             event_writer.send(InputReceivedEvent { origin, input });
         }
     }
 }
 
 pub fn receive_player_heaviness(
-    mut server: ResMut<RenetServer>,
+    mut server: ResMut<RoomServer>,
     lobby: Res<Lobby>,
     mut event_writer: EventWriter<HeavinessReceivedEvent>,
 ) {
     for &origin in lobby.players.keys() {
         while let Some(message) = server.receive_message(origin, ClientChannel::PlayerHeaviness) {
-            // TODO unwraps
-            let heaviness: bool = bincode::deserialize(&message).unwrap();
+            let Ok(heaviness) = bincode::deserialize::<bool>(&message) else {
+                continue;
+            };
             event_writer.send(HeavinessReceivedEvent { origin, heaviness });
         }
     }
 }
 
 pub fn broadcast_players_inputs(
-    mut server: ResMut<RenetServer>,
+    mut server: ResMut<RoomServer>,
     mut event_reader: EventReader<InputReceivedEvent>,
 ) {
     for InputReceivedEvent { origin, input } in event_reader.iter() {
@@ -68,7 +70,7 @@ pub fn broadcast_players_inputs(
 }
 
 pub fn broadcast_players_heaviness(
-    mut server: ResMut<RenetServer>,
+    mut server: ResMut<RoomServer>,
     mut event_reader: EventReader<HeavinessReceivedEvent>,
 ) {
     for HeavinessReceivedEvent { origin, heaviness } in event_reader.iter() {
@@ -78,15 +80,33 @@ pub fn broadcast_players_heaviness(
 }
 
 pub(crate) fn broadcast_networked_entities(
-    mut server: ResMut<RenetServer>,
+    mut server: ResMut<RoomServer>,
     lobby: Res<Lobby>,
     query: Query<(&Transform, &DirectionVector, &Heavy), With<Ball>>,
+    bodies: Query<(Entity, &ReplicatedBody, &Transform, &Velocity)>,
+    mut mode_state: ResMut<ModeStateSnapshot>,
+    mut collisions: EventReader<BallCollision>,
 ) {
-    let mut map = HashMap::new();
+    let mut entities = NetworkedEntities {
+        mode_state: mode_state.0.take(),
+        collisions: collisions.iter().copied().collect(),
+        ..default()
+    };
     for (id, data) in lobby.players.iter() {
-        let (transform, direction, heavy) = query.get(data.entity.unwrap()).unwrap();
-        map.insert(id, (transform.translation, direction, heavy.heaviness));
+        let Some(Ok((transform, direction, heavy))) = data.entity.map(|entity| query.get(entity))
+        else {
+            continue;
+        };
+        entities
+            .balls
+            .insert(*id, (transform.translation, *direction, *heavy));
+    }
+    for (entity, body, transform, velocity) in bodies.iter() {
+        entities.bodies.insert(
+            entity.to_bits(),
+            (body.0, transform.translation.truncate(), velocity.linvel),
+        );
     }
-    let message = bincode::serialize(&map).unwrap();
+    let message = bincode::serialize(&entities).unwrap();
     server.broadcast_message(ServerChannel::NetworkedEntities, message);
 }
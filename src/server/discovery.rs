@@ -0,0 +1,73 @@
+use std::net::{Ipv4Addr, UdpSocket};
+
+use bevy::prelude::*;
+use bevy_renet::renet::RenetServer;
+
+use crate::{
+    discovery::{DiscoveryMessage, ServerAnnouncement, DISCOVERY_PORT},
+    protocol::protocol_id,
+    GameState,
+};
+
+use super::room::Rooms;
+
+/// Answers the discovery queries broadcast by the clients of the local network
+pub struct DiscoveryResponderPlugin {
+    pub name: String,
+    /// Port of the game server
+    pub port: u16,
+}
+
+impl Plugin for DiscoveryResponderPlugin {
+    fn build(&self, app: &mut App) {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT)).and_then(|socket| {
+            socket.set_nonblocking(true)?;
+            Ok(socket)
+        });
+        match socket {
+            Ok(socket) => {
+                println!("Answering discovery queries on port {}", DISCOVERY_PORT);
+                app.insert_resource(DiscoveryResponder {
+                    socket,
+                    name: self.name.clone(),
+                    port: self.port,
+                })
+                .add_systems(Update, answer_discovery_queries);
+            }
+            Err(error) => eprintln!("Could not start the discovery responder: {}", error),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct DiscoveryResponder {
+    socket: UdpSocket,
+    name: String,
+    port: u16,
+}
+
+fn answer_discovery_queries(
+    responder: Res<DiscoveryResponder>,
+    server: Res<RenetServer>,
+    rooms: Res<Rooms>,
+) {
+    let mut buffer = [0; 64];
+    while let Ok((len, origin)) = responder.socket.recv_from(&mut buffer) {
+        let Some(DiscoveryMessage::Query) = DiscoveryMessage::decode(&buffer[..len]) else {
+            continue;
+        };
+        let rooms = rooms.infos();
+        let announcement = DiscoveryMessage::Announcement(ServerAnnouncement {
+            name: responder.name.clone(),
+            protocol_id: protocol_id(),
+            port: responder.port,
+            players: server.clients_id().len(),
+            rooms: rooms.len(),
+            games_in_progress: rooms
+                .iter()
+                .filter(|room| room.state != GameState::Lobby)
+                .count(),
+        });
+        let _ = responder.socket.send_to(&announcement.encode(), origin);
+    }
+}
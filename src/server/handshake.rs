@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use bevy_renet::renet::{transport::NetcodeServerTransport, RenetServer};
+
+use crate::{client_of, protocol::ProtocolInfo};
+
+use super::{channel::ServerChannel, ServerMessage};
+
+/// Time left to rejected clients to receive the reason of their rejection before being disconnected
+const REJECTION_DELAY: Duration = Duration::from_secs(1);
+
+/// Disconnects the clients that do not speak the protocol of the server
+pub struct HandshakePlugin;
+
+impl Plugin for HandshakePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RejectedClients::default())
+            .add_systems(Update, disconnect_rejected_clients);
+    }
+}
+
+/// Clients waiting to be disconnected, along with the time of their disconnection
+#[derive(Debug, Default, Resource)]
+pub struct RejectedClients(HashMap<u64, Instant>);
+
+impl RejectedClients {
+    /// Tells the client why it is rejected and disconnects it shortly after
+    pub fn reject(&mut self, server: &mut RenetServer, client_id: u64, reason: String) {
+        println!("Rejecting client {}: {}", client_id, reason);
+        let message = bincode::serialize(&ServerMessage::Rejected { reason }).unwrap();
+        server.send_message(client_id, ServerChannel::ServerMessages, message);
+        self.0.insert(client_id, Instant::now() + REJECTION_DELAY);
+    }
+
+    /// Whether the client is waiting to be disconnected, its messages being ignored meanwhile
+    pub fn contains(&self, client_id: u64) -> bool {
+        self.0.contains_key(&client_id)
+    }
+}
+
+/// Refuses the client ids with slot bits, which would pass the client for a local player of
+/// another client
+pub fn check_client_id(client_id: u64) -> Result<(), String> {
+    if client_of(client_id) == client_id {
+        Ok(())
+    } else {
+        Err(format!("Invalid client id {client_id}"))
+    }
+}
+
+/// Checks the protocol announced by the client in its user data against the one of the server
+pub fn check_client_protocol(
+    transport: &NetcodeServerTransport,
+    client_id: u64,
+) -> Result<(), String> {
+    let Some(user_data) = transport.user_data(client_id) else {
+        return Err("The client did not tell its game version".to_owned());
+    };
+    let client = ProtocolInfo::from_user_data(&user_data);
+    let server = ProtocolInfo::current();
+    if client == server {
+        Ok(())
+    } else if client.version != server.version {
+        Err(format!(
+            "Incompatible game version: the server runs version {} and the client version {}",
+            server.version, client.version
+        ))
+    } else {
+        Err(format!(
+            "Incompatible game build: both run version {} but their protocols differ ({:016x} on the server, {:016x} on the client)",
+            server.version, server.id, client.id
+        ))
+    }
+}
+
+fn disconnect_rejected_clients(
+    mut server: ResMut<RenetServer>,
+    mut rejected: ResMut<RejectedClients>,
+) {
+    let now = Instant::now();
+    rejected.0.retain(|&client_id, disconnection| {
+        if now < *disconnection {
+            return true;
+        }
+        server.disconnect(client_id);
+        false
+    });
+}
@@ -0,0 +1,796 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    time::{Duration, SystemTime},
+};
+
+use bevy::{
+    hierarchy::HierarchyPlugin, prelude::*, time::common_conditions::on_timer,
+    transform::TransformPlugin,
+};
+use bevy_renet::renet::{transport::NetcodeServerTransport, RenetServer, ServerEvent};
+use renet_visualizer::RenetServerVisualizer;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    client::{channel::ClientChannel, ClientMessage},
+    client_of,
+    map::Map,
+    mode::GameModes,
+    player_id, GameState, Lobby, Mode, PlayerInput, PlayerSlot, Ruleset, FIRST_SLOT,
+    MAX_LOCAL_PLAYERS,
+};
+
+use super::{
+    channel::ServerChannel,
+    handshake::{check_client_id, check_client_protocol, RejectedClients},
+    RoomPlugin, ServerMessage,
+};
+
+const MAX_ROOMS: usize = 32;
+pub const MAX_ROOM_PLAYERS: usize = 16;
+const MAX_ROOM_NAME_LENGTH: usize = 32;
+/// Messages kept per client and channel inside of a room before the oldest ones are dropped
+const MAX_QUEUED_MESSAGES: usize = 64;
+const DEFAULT_ROOM_NAME: &str = "Main";
+const ROOM_CODE_LENGTH: usize = 5;
+/// Characters room codes are made of, leaving out the ones that are easily mistaken for others
+const ROOM_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// Time between two updates of the pings shown to the members of the rooms
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+
+pub type RoomId = u32;
+
+/// Summary of a room, as shown to the clients browsing the rooms of the server
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub id: RoomId,
+    /// Short code players can share to join the room
+    pub code: String,
+    pub name: String,
+    /// Whether a password is needed to join the room
+    pub locked: bool,
+    pub players: usize,
+    pub max_players: usize,
+    pub map: String,
+    pub mode: Mode,
+    pub state: GameState,
+}
+
+/// Sent in the world of a room when a player enters it
+#[derive(Event)]
+pub struct PlayerJoinedEvent {
+    pub player_id: u64,
+    /// Whether the player only watches the matches
+    pub spectator: bool,
+}
+
+/// Sent in the world of a room when a player leaves it or their client disconnects
+#[derive(Event)]
+pub struct PlayerLeftEvent {
+    pub player_id: u64,
+}
+
+/// Hosts the rooms of the server and routes the messages of the clients to their room
+pub struct RoomsPlugin;
+
+impl Plugin for RoomsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Rooms::default())
+            .add_systems(Startup, create_default_room)
+            .add_systems(
+                Update,
+                (
+                    receive_server_events,
+                    receive_client_messages,
+                    forward_room_messages,
+                    update_rooms,
+                    send_room_messages,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, broadcast_pings.run_if(on_timer(PING_INTERVAL)));
+    }
+}
+
+/// Checks that the room can be played with the ruleset, along with the rules of its mode
+pub(crate) fn check_ruleset(ruleset: &Ruleset, map: &Map, modes: &GameModes) -> Result<(), String> {
+    if ruleset.min_players == 0
+        || ruleset.min_players > ruleset.max_players
+        || ruleset.max_players > MAX_ROOM_PLAYERS
+    {
+        return Err("Invalid ruleset".to_owned());
+    }
+    // The comparisons alone let NaN through, which would end up in the masses of the balls
+    let stamina_rules = [
+        ruleset.stamina_duration,
+        ruleset.stamina_recovery,
+        ruleset.exhaustion_threshold,
+        ruleset.heavy_mass_factor,
+    ];
+    if !stamina_rules.iter().all(|value| value.is_finite())
+        || ruleset.stamina_duration <= 0.
+        || ruleset.stamina_recovery <= 0.
+        || !(0. ..=1.).contains(&ruleset.exhaustion_threshold)
+        || ruleset.heavy_mass_factor < 1.
+    {
+        return Err("Invalid stamina rules".to_owned());
+    }
+    if !ruleset.collision_knockback.is_finite() || ruleset.collision_knockback < 0. {
+        return Err("Invalid collision knockback".to_owned());
+    }
+    let mode = modes.get(ruleset.mode);
+    if ruleset.min_players < mode.min_players() {
+        return Err(format!(
+            "The {:?} mode needs at least {} players",
+            ruleset.mode,
+            mode.min_players()
+        ));
+    }
+    mode.check(ruleset, map)
+}
+
+/// Stands in for the `RenetServer` inside of a room, only reaching the members of the room
+///
+/// Messages are received from and sent to players, the main world telling their clients apart
+#[derive(Debug, Default, Resource)]
+pub struct RoomServer {
+    received: HashMap<(u64, u8), VecDeque<Vec<u8>>>,
+    /// Messages waiting to be sent, to a single player or to every member when there is no recipient
+    sent: Vec<(Option<u64>, u8, Vec<u8>)>,
+    /// Members to be removed from the room, along with the reason
+    kicked: Vec<(u64, String)>,
+}
+
+impl RoomServer {
+    pub fn receive_message<I: Into<u8>>(
+        &mut self,
+        client_id: u64,
+        channel_id: I,
+    ) -> Option<Vec<u8>> {
+        self.received
+            .get_mut(&(client_id, channel_id.into()))?
+            .pop_front()
+    }
+
+    pub fn send_message<I: Into<u8>>(&mut self, client_id: u64, channel_id: I, message: Vec<u8>) {
+        self.sent
+            .push((Some(client_id), channel_id.into(), message));
+    }
+
+    pub fn broadcast_message<I: Into<u8>>(&mut self, channel_id: I, message: Vec<u8>) {
+        self.sent.push((None, channel_id.into(), message));
+    }
+
+    /// Removes the client of the player from the room along with its other local players,
+    /// sending it back to the room browser
+    pub fn kick(&mut self, client_id: u64, reason: String) {
+        self.kicked.push((client_id, reason));
+    }
+
+    fn push_received(&mut self, client_id: u64, channel_id: u8, message: Vec<u8>) {
+        let queue = self.received.entry((client_id, channel_id)).or_default();
+        if queue.len() >= MAX_QUEUED_MESSAGES {
+            queue.pop_front();
+        }
+        queue.push_back(message);
+    }
+
+    fn forget(&mut self, client_id: u64) {
+        self.received.retain(|(id, _), _| *id != client_id);
+    }
+}
+
+/// A game hosted by the server, simulated in its own world with its own physics context
+pub struct Room {
+    name: String,
+    code: String,
+    password: Option<String>,
+    /// Players in the room, the local players of a client being members on their own
+    members: HashSet<u64>,
+    /// Persistent rooms stay open when the last player leaves them
+    persistent: bool,
+    world: World,
+}
+
+impl Room {
+    fn new(
+        name: String,
+        code: String,
+        password: Option<String>,
+        map: Map,
+        ruleset: Ruleset,
+    ) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin))
+            .insert_resource(map)
+            .insert_resource(ruleset)
+            .add_plugins(RoomPlugin);
+        app.finish();
+        app.cleanup();
+        Room {
+            name,
+            code,
+            password,
+            members: HashSet::new(),
+            persistent: false,
+            world: std::mem::take(&mut app.world),
+        }
+    }
+
+    fn info(&self, id: RoomId) -> RoomInfo {
+        let ruleset = self.world.resource::<Ruleset>();
+        RoomInfo {
+            id,
+            code: self.code.clone(),
+            name: self.name.clone(),
+            locked: self.password.is_some(),
+            players: self.player_count(),
+            max_players: ruleset.max_players,
+            map: self.world.resource::<Map>().name.clone(),
+            mode: ruleset.mode,
+            state: *self.world.resource::<State<GameState>>().get(),
+        }
+    }
+
+    /// Members playing the matches, the members the room has not seen yet counting as players
+    fn player_count(&self) -> usize {
+        let spectators = self
+            .world
+            .resource::<Lobby>()
+            .players
+            .values()
+            .filter(|data| data.spectator)
+            .count();
+        self.members.len().saturating_sub(spectators)
+    }
+
+    fn is_full(&self) -> bool {
+        self.player_count() >= self.world.resource::<Ruleset>().max_players
+    }
+
+    /// Clients of the members, each client being listed once whatever its number of local players
+    fn clients(&self) -> HashSet<u64> {
+        self.members.iter().copied().map(client_of).collect()
+    }
+
+    fn update(&mut self) {
+        self.world.run_schedule(Main);
+        self.world.clear_trackers();
+    }
+}
+
+#[derive(Resource)]
+pub struct Rooms {
+    rooms: HashMap<RoomId, Room>,
+    /// Room each player is currently in
+    memberships: HashMap<u64, RoomId>,
+    next_id: RoomId,
+    /// Modes the rulesets of the new rooms are checked against before building the rooms
+    modes: GameModes,
+}
+
+impl Default for Rooms {
+    fn default() -> Self {
+        Self {
+            rooms: HashMap::new(),
+            memberships: HashMap::new(),
+            next_id: 0,
+            modes: GameModes::builtin(),
+        }
+    }
+}
+
+impl Rooms {
+    pub fn infos(&self) -> Vec<RoomInfo> {
+        let mut infos: Vec<_> = self.rooms.iter().map(|(&id, room)| room.info(id)).collect();
+        infos.sort_by_key(|info| info.id);
+        infos
+    }
+
+    fn create(
+        &mut self,
+        name: String,
+        password: Option<String>,
+        map: Map,
+        ruleset: Ruleset,
+    ) -> Result<RoomId, String> {
+        if self.rooms.len() >= MAX_ROOMS {
+            return Err("The server can't host any more rooms".to_owned());
+        }
+        check_ruleset(&ruleset, &map, &self.modes)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        let name = name.trim();
+        let name = if name.is_empty() || name.chars().count() > MAX_ROOM_NAME_LENGTH {
+            format!("Room {}", id)
+        } else {
+            name.to_owned()
+        };
+        let password = password.filter(|password| !password.is_empty());
+        let code = self.generate_code(id);
+        let map_name = map.name.clone();
+        let room = Room::new(name, code, password, map, ruleset);
+        println!(
+            "Creating room {} \"{}\" with code {} on map {}",
+            id, room.name, room.code, map_name
+        );
+        self.rooms.insert(id, room);
+        Ok(id)
+    }
+
+    fn generate_code(&self, seed: RoomId) -> String {
+        let time = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        let mut state = (time ^ seed as u64) | 1;
+        loop {
+            let code: String = (0..ROOM_CODE_LENGTH)
+                .map(|_| {
+                    // xorshift64
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    ROOM_CODE_ALPHABET[(state % ROOM_CODE_ALPHABET.len() as u64) as usize] as char
+                })
+                .collect();
+            if !self.rooms.values().any(|room| room.code == code) {
+                return code;
+            }
+        }
+    }
+
+    fn find_by_code(&self, code: &str) -> Option<RoomId> {
+        let code = code.trim();
+        self.rooms
+            .iter()
+            .find(|(_, room)| room.code.eq_ignore_ascii_case(code))
+            .map(|(&id, _)| id)
+    }
+
+    fn join(
+        &mut self,
+        client_id: u64,
+        room_id: RoomId,
+        password: Option<&str>,
+        spectator: bool,
+    ) -> Result<RoomInfo, String> {
+        if self.memberships.get(&client_id) == Some(&room_id) {
+            return Err("Already in this room".to_owned());
+        }
+        match self.rooms.get(&room_id) {
+            None => return Err("This room doesn't exist".to_owned()),
+            // Spectators are let in full rooms
+            Some(room) if !spectator && room.is_full() => {
+                return Err("This room is full".to_owned())
+            }
+            Some(room) if room.password.is_some() && room.password.as_deref() != password => {
+                return Err("Wrong password".to_owned())
+            }
+            Some(_) => (),
+        }
+        self.leave_client(client_id);
+        let room = self.rooms.get_mut(&room_id).unwrap();
+        room.members.insert(client_id);
+        room.world.send_event(PlayerJoinedEvent {
+            player_id: client_id,
+            spectator,
+        });
+        self.memberships.insert(client_id, room_id);
+        Ok(room.info(room_id))
+    }
+
+    /// Brings another local player of the client in the room the client is in
+    fn add_local_player(&mut self, client_id: u64, slot: PlayerSlot) -> Result<(), String> {
+        let player_id = player_id(client_id, slot);
+        if slot == FIRST_SLOT || slot as usize >= MAX_LOCAL_PLAYERS {
+            return Err("Invalid local player".to_owned());
+        }
+        if self.memberships.contains_key(&player_id) {
+            return Err("This local player is already in the room".to_owned());
+        }
+        let Some(&room_id) = self.memberships.get(&client_id) else {
+            return Err("Join a room first".to_owned());
+        };
+        let room = self.rooms.get_mut(&room_id).unwrap();
+        if *room.world.resource::<State<GameState>>().get() != GameState::Lobby {
+            return Err("Local players can only join in the lobby".to_owned());
+        }
+        if room.is_full() {
+            return Err("This room is full".to_owned());
+        }
+        room.members.insert(player_id);
+        room.world.send_event(PlayerJoinedEvent {
+            player_id,
+            spectator: false,
+        });
+        self.memberships.insert(player_id, room_id);
+        Ok(())
+    }
+
+    /// Removes the player from its room, closing the room if nobody is left in it
+    fn leave(&mut self, player_id: u64) {
+        let Some(room_id) = self.memberships.remove(&player_id) else {
+            return;
+        };
+        let Some(room) = self.rooms.get_mut(&room_id) else {
+            return;
+        };
+        room.members.remove(&player_id);
+        room.world.resource_mut::<RoomServer>().forget(player_id);
+        room.world.send_event(PlayerLeftEvent { player_id });
+        if room.members.is_empty() && !room.persistent {
+            println!("Closing empty room {}", room_id);
+            self.rooms.remove(&room_id);
+        }
+    }
+
+    /// Removes every local player of the client from its room
+    fn leave_client(&mut self, client_id: u64) {
+        let players: Vec<u64> = self
+            .memberships
+            .keys()
+            .copied()
+            .filter(|&player_id| client_of(player_id) == client_id)
+            .collect();
+        for player_id in players {
+            self.leave(player_id);
+        }
+    }
+
+    /// Clients in the room the client is in, including the client itself
+    pub fn clients_in_room_of(&self, client_id: u64) -> Option<HashSet<u64>> {
+        let room_id = self.memberships.get(&client_id)?;
+        self.rooms.get(room_id).map(Room::clients)
+    }
+
+    fn room_of_mut(&mut self, player_id: u64) -> Option<&mut Room> {
+        let room_id = *self.memberships.get(&player_id)?;
+        self.rooms.get_mut(&room_id)
+    }
+}
+
+fn create_default_room(mut rooms: ResMut<Rooms>) {
+    let room_id = rooms
+        .create(
+            DEFAULT_ROOM_NAME.to_owned(),
+            None,
+            Map::default(),
+            Ruleset::default(),
+        )
+        .unwrap();
+    rooms.rooms.get_mut(&room_id).unwrap().persistent = true;
+}
+
+fn send_server_message(server: &mut RenetServer, client_id: u64, message: &ServerMessage) {
+    let message = bincode::serialize(message).unwrap();
+    server.send_message(client_id, ServerChannel::ServerMessages, message);
+}
+
+fn receive_server_events(
+    mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    transport: Res<NetcodeServerTransport>,
+    mut rooms: ResMut<Rooms>,
+    mut rejected: ResMut<RejectedClients>,
+    mut visualizer: ResMut<RenetServerVisualizer<200>>,
+) {
+    for event in server_events.iter() {
+        match event {
+            ServerEvent::ClientConnected { client_id } => {
+                let checked = check_client_id(*client_id)
+                    .and_then(|()| check_client_protocol(&transport, *client_id));
+                if let Err(reason) = checked {
+                    rejected.reject(&mut server, *client_id, reason);
+                    continue;
+                }
+                println!("Player joined with client id {}", client_id);
+                visualizer.add_client(*client_id);
+                send_server_message(
+                    &mut server,
+                    *client_id,
+                    &ServerMessage::RoomList {
+                        rooms: rooms.infos(),
+                    },
+                );
+            }
+            ServerEvent::ClientDisconnected { client_id, reason } => {
+                println!(
+                    "Player with client id {} left with reason \"{}\"",
+                    client_id, reason
+                );
+                visualizer.remove_client(*client_id);
+                rooms.leave_client(*client_id);
+            }
+        }
+    }
+}
+
+fn receive_client_messages(
+    mut server: ResMut<RenetServer>,
+    mut rooms: ResMut<Rooms>,
+    rejected: Res<RejectedClients>,
+) {
+    for client_id in server.clients_id() {
+        if rejected.contains(client_id) {
+            continue;
+        }
+        while let Some(message) = server.receive_message(client_id, ClientChannel::ClientMessages) {
+            let Ok(decoded) = bincode::deserialize::<ClientMessage>(&message) else {
+                continue;
+            };
+            let response = match decoded {
+                ClientMessage::ListRooms => ServerMessage::RoomList {
+                    rooms: rooms.infos(),
+                },
+                ClientMessage::CreateRoom {
+                    name,
+                    password,
+                    map,
+                    ruleset,
+                } => match Map::find(&map)
+                    .ok_or_else(|| format!("Unknown map \"{}\"", map))
+                    .and_then(|map| rooms.create(name, password.clone(), map, ruleset))
+                    .and_then(|room_id| rooms.join(client_id, room_id, password.as_deref(), false))
+                {
+                    Ok(room) => ServerMessage::JoinedRoom { room },
+                    Err(reason) => ServerMessage::RoomError { reason },
+                },
+                ClientMessage::JoinRoom {
+                    room_id,
+                    password,
+                    spectator,
+                } => match rooms.join(client_id, room_id, password.as_deref(), spectator) {
+                    Ok(room) => ServerMessage::JoinedRoom { room },
+                    Err(reason) => ServerMessage::RoomError { reason },
+                },
+                ClientMessage::JoinRoomByCode {
+                    code,
+                    password,
+                    spectator,
+                } => match rooms
+                    .find_by_code(&code)
+                    .ok_or_else(|| format!("No room with code \"{}\"", code))
+                    .and_then(|room_id| {
+                        rooms.join(client_id, room_id, password.as_deref(), spectator)
+                    }) {
+                    Ok(room) => ServerMessage::JoinedRoom { room },
+                    Err(reason) => ServerMessage::RoomError { reason },
+                },
+                ClientMessage::LeaveRoom => {
+                    rooms.leave_client(client_id);
+                    send_server_message(&mut server, client_id, &ServerMessage::LeftRoom);
+                    ServerMessage::RoomList {
+                        rooms: rooms.infos(),
+                    }
+                }
+                ClientMessage::AddLocalPlayer { slot } => {
+                    match rooms.add_local_player(client_id, slot) {
+                        Ok(()) => continue,
+                        Err(reason) => ServerMessage::RoomError { reason },
+                    }
+                }
+                ClientMessage::RemoveLocalPlayer { slot } => {
+                    // The first local player only leaves along with the client
+                    if slot != FIRST_SLOT {
+                        rooms.leave(player_id(client_id, slot));
+                    }
+                    continue;
+                }
+                // Handled by the room the client is in, as coming from the player they are about
+                ClientMessage::SetProfile { slot, .. }
+                | ClientMessage::SetReady { slot, .. }
+                | ClientMessage::SetSpectator { slot, .. }
+                | ClientMessage::ChooseTeam { slot, .. } => {
+                    forward_client_message(&mut rooms, player_id(client_id, slot), &message);
+                    continue;
+                }
+                ClientMessage::ChangeRoomSettings { .. }
+                | ClientMessage::KickPlayer { .. }
+                | ClientMessage::StartMatch => {
+                    forward_client_message(&mut rooms, client_id, &message);
+                    continue;
+                }
+            };
+            send_server_message(&mut server, client_id, &response);
+        }
+    }
+}
+
+fn forward_client_message(rooms: &mut Rooms, player_id: u64, message: &[u8]) {
+    if let Some(room) = rooms.room_of_mut(player_id) {
+        room.world.resource_mut::<RoomServer>().push_received(
+            player_id,
+            ClientChannel::ClientMessages.into(),
+            message.to_vec(),
+        );
+    }
+}
+
+/// Moves the gameplay messages of each local player to the room it is in, dropping them otherwise
+fn forward_room_messages(
+    mut server: ResMut<RenetServer>,
+    mut rooms: ResMut<Rooms>,
+    rejected: Res<RejectedClients>,
+) {
+    for client_id in server.clients_id() {
+        if rejected.contains(client_id) {
+            continue;
+        }
+        let decoders: [(u8, GameplayDecoder); 2] = [
+            (
+                ClientChannel::PlayerInput.into(),
+                decode_gameplay_message::<PlayerInput>,
+            ),
+            (
+                ClientChannel::PlayerHeaviness.into(),
+                decode_gameplay_message::<bool>,
+            ),
+        ];
+        for (channel_id, decode) in decoders {
+            while let Some(message) = server.receive_message(client_id, channel_id) {
+                let Some((slot, payload)) = decode(&message) else {
+                    continue;
+                };
+                let player_id = player_id(client_id, slot);
+                if let Some(room) = rooms.room_of_mut(player_id) {
+                    room.world
+                        .resource_mut::<RoomServer>()
+                        .push_received(player_id, channel_id, payload);
+                }
+            }
+        }
+    }
+}
+
+/// Reads a gameplay message into the slot of the player it comes from and the payload the room
+/// reads, none if it is malformed
+type GameplayDecoder = fn(&[u8]) -> Option<(PlayerSlot, Vec<u8>)>;
+
+/// Gameplay messages are serialized along with the slot of the player they come from, and only
+/// the valid ones reach the rooms, stripped of their slot
+fn decode_gameplay_message<T: Serialize + DeserializeOwned>(
+    message: &[u8],
+) -> Option<(PlayerSlot, Vec<u8>)> {
+    let (slot, value) = bincode::deserialize::<(PlayerSlot, T)>(message).ok()?;
+    Some((slot, bincode::serialize(&value).unwrap()))
+}
+
+/// Tells the members of each room the round trip times of the members, which are the ones of
+/// their clients
+fn broadcast_pings(mut server: ResMut<RenetServer>, rooms: Res<Rooms>) {
+    for room in rooms.rooms.values() {
+        let pings: HashMap<u64, u32> = room
+            .members
+            .iter()
+            .filter_map(|&player_id| {
+                let info = server.network_info(client_of(player_id)).ok()?;
+                Some((player_id, info.rtt as u32))
+            })
+            .collect();
+        for client_id in room.clients() {
+            send_server_message(
+                &mut server,
+                client_id,
+                &ServerMessage::Pings {
+                    pings: pings.clone(),
+                },
+            );
+        }
+    }
+}
+
+fn update_rooms(world: &mut World) {
+    world.resource_scope(|_, mut rooms: Mut<Rooms>| {
+        for room in rooms.rooms.values_mut() {
+            room.update();
+        }
+    });
+}
+
+fn send_room_messages(mut server: ResMut<RenetServer>, mut rooms: ResMut<Rooms>) {
+    let mut kicked = Vec::new();
+    for room in rooms.rooms.values_mut() {
+        let mut room_server = room.world.resource_mut::<RoomServer>();
+        kicked.append(&mut room_server.kicked);
+        let sent = std::mem::take(&mut room_server.sent);
+        for (recipient, channel_id, message) in sent {
+            match recipient {
+                Some(player_id) => {
+                    if room.members.contains(&player_id) {
+                        server.send_message(client_of(player_id), channel_id, message);
+                    }
+                }
+                None => {
+                    for client_id in room.clients() {
+                        server.send_message(client_id, channel_id, message.clone());
+                    }
+                }
+            }
+        }
+    }
+    for (player_id, reason) in kicked {
+        let client_id = client_of(player_id);
+        rooms.leave_client(client_id);
+        send_server_message(&mut server, client_id, &ServerMessage::LeftRoom);
+        send_server_message(&mut server, client_id, &ServerMessage::RoomError { reason });
+        send_server_message(
+            &mut server,
+            client_id,
+            &ServerMessage::RoomList {
+                rooms: rooms.infos(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Teams;
+
+    fn check(ruleset: &Ruleset, map: &Map) -> Result<(), String> {
+        check_ruleset(ruleset, map, &GameModes::builtin())
+    }
+
+    #[test]
+    fn default_ruleset_is_valid() {
+        assert!(check(&Ruleset::default(), &Map::default()).is_ok());
+    }
+
+    #[test]
+    fn player_counts_are_checked() {
+        let map = Map::default();
+        let ruleset = Ruleset {
+            min_players: 4,
+            max_players: 3,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+        let ruleset = Ruleset {
+            max_players: MAX_ROOM_PLAYERS + 1,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+    }
+
+    #[test]
+    fn non_finite_rules_are_refused() {
+        let map = Map::default();
+        let ruleset = Ruleset {
+            stamina_duration: f32::NAN,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+        let ruleset = Ruleset {
+            heavy_mass_factor: f32::INFINITY,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+        let ruleset = Ruleset {
+            collision_knockback: f32::NAN,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+    }
+
+    #[test]
+    fn modes_need_their_map_layout() {
+        let map = Map {
+            capture_zone: None,
+            pitch: None,
+            ..default()
+        };
+        let ruleset = Ruleset {
+            mode: Mode::Capture,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+        let ruleset = Ruleset {
+            mode: Mode::Football,
+            teams: Teams::Two,
+            ..default()
+        };
+        assert!(check(&ruleset, &map).is_err());
+    }
+}
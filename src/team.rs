@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Colors of the teams, overriding the colors chosen by the players
+pub const TEAM_COLORS: [Color; 4] = [
+    Color::rgb(0.85, 0.25, 0.25),
+    Color::rgb(0.25, 0.45, 0.85),
+    Color::rgb(0.3, 0.75, 0.3),
+    Color::rgb(0.95, 0.75, 0.2),
+];
+pub const TEAM_NAMES: [&str; 4] = ["Red", "Blue", "Green", "Yellow"];
+
+/// How the players of a room are split into teams
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Teams {
+    #[default]
+    FreeForAll,
+    Two,
+    Four,
+}
+
+impl Teams {
+    pub const ALL: [Teams; 3] = [Teams::FreeForAll, Teams::Two, Teams::Four];
+
+    /// Number of teams, zero when everyone plays for themselves
+    pub fn count(self) -> u8 {
+        match self {
+            Teams::FreeForAll => 0,
+            Teams::Two => 2,
+            Teams::Four => 4,
+        }
+    }
+}
+
+pub fn team_color(team: u8) -> Color {
+    TEAM_COLORS[team as usize % TEAM_COLORS.len()]
+}
+
+pub fn team_name(team: u8) -> &'static str {
+    TEAM_NAMES[team as usize % TEAM_NAMES.len()]
+}
+
+/// Puts every player without a team into the smallest team
+pub fn balance_teams<'a>(teams: Teams, players: impl Iterator<Item = &'a mut Option<u8>>) {
+    let count = teams.count();
+    let mut players: Vec<_> = players.collect();
+    if count == 0 {
+        for team in players {
+            *team = None;
+        }
+        return;
+    }
+    let mut sizes = vec![0; count as usize];
+    for team in players.iter_mut() {
+        match team {
+            Some(index) if *index < count => sizes[*index as usize] += 1,
+            _ => **team = None,
+        }
+    }
+    for team in players.into_iter().filter(|team| team.is_none()) {
+        let (smallest, _) = sizes
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, &size)| size)
+            .unwrap();
+        sizes[smallest] += 1;
+        *team = Some(smallest as u8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_for_all_clears_the_teams() {
+        let mut players = [Some(0), None, Some(3)];
+        balance_teams(Teams::FreeForAll, players.iter_mut());
+        assert_eq!(players, [None, None, None]);
+    }
+
+    #[test]
+    fn unknown_teams_are_reassigned() {
+        let mut players = [Some(0), Some(3)];
+        balance_teams(Teams::Two, players.iter_mut());
+        assert_eq!(players, [Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn players_fill_the_smallest_teams() {
+        let mut players = [Some(0), Some(0), Some(1), None, None, None];
+        balance_teams(Teams::Two, players.iter_mut());
+        assert_eq!(
+            players,
+            [Some(0), Some(0), Some(1), Some(1), Some(0), Some(1)]
+        );
+    }
+}